@@ -0,0 +1,532 @@
+use std::fmt;
+
+use inquire::{validator::Validation, DateSelect, MultiSelect, Select, Text};
+use ulid::Ulid;
+
+use crate::prelude::*;
+
+/// A search over every card in the tree, made of terms which must all match, such as
+/// `deck:French::Vocab tag:verb due:<3 front:*ir "to climb" -is:suspended`.
+#[derive(Clone, Debug)]
+pub struct Query {
+    terms: Vec<Term>,
+}
+
+#[derive(Clone, Debug)]
+struct Term {
+    negated: bool,
+    filter: Filter,
+}
+
+#[derive(Clone, Debug)]
+enum Filter {
+    /// The pattern appears in the front or back.
+    Text(String),
+    Front(String),
+    Back(String),
+    /// The card is in the deck at the given `::`-separated path, or beneath it.
+    Deck(String),
+    Tag(String),
+    /// The number of days until the card is due.
+    Due(Comparison),
+    Interval(Comparison),
+    Is(CardState),
+}
+
+#[derive(Clone, Copy, Debug)]
+enum CardState {
+    New,
+    Due,
+    Review,
+    /// In a learning or relearning step.
+    Learning,
+    Suspended,
+    Buried,
+}
+
+#[derive(Clone, Copy, Debug)]
+struct Comparison {
+    op: Op,
+    value: i64,
+}
+
+#[derive(Clone, Copy, Debug)]
+enum Op {
+    Less,
+    LessOrEqual,
+    Equal,
+    GreaterOrEqual,
+    Greater,
+}
+
+/// Why a search query couldn't be understood.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum QueryError {
+    UnclosedQuote,
+    UnknownKey(String),
+    MissingValue(String),
+    InvalidNumber(String),
+    UnknownState(String),
+}
+
+impl fmt::Display for QueryError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::UnclosedQuote => write!(f, "a quote is never closed"),
+            Self::UnknownKey(key) => write!(f, "unknown search key `{}`", key),
+            Self::MissingValue(key) => write!(f, "`{}:` needs a value after it", key),
+            Self::InvalidNumber(value) => write!(f, "`{}` is not a whole number", value),
+            Self::UnknownState(state) => write!(f, "unknown state `is:{}`", state),
+        }
+    }
+}
+
+impl std::error::Error for QueryError {}
+
+impl Comparison {
+    /// A number of days, optionally preceded by `<`, `<=`, `=`, `>=` or `>`.
+    fn parse(text: &str) -> Result<Self, QueryError> {
+        let (op, number) = [
+            ("<=", Op::LessOrEqual),
+            (">=", Op::GreaterOrEqual),
+            ("<", Op::Less),
+            (">", Op::Greater),
+            ("=", Op::Equal),
+        ]
+        .into_iter()
+        .find_map(|(prefix, op)| text.strip_prefix(prefix).map(|rest| (op, rest)))
+        .unwrap_or((Op::Equal, text));
+
+        let value = number
+            .parse()
+            .map_err(|_| QueryError::InvalidNumber(number.to_owned()))?;
+        Ok(Self { op, value })
+    }
+
+    fn matches(self, value: i64) -> bool {
+        match self.op {
+            Op::Less => value < self.value,
+            Op::LessOrEqual => value <= self.value,
+            Op::Equal => value == self.value,
+            Op::GreaterOrEqual => value >= self.value,
+            Op::Greater => value > self.value,
+        }
+    }
+}
+
+impl Filter {
+    fn parse(key: Option<&str>, value: String) -> Result<Self, QueryError> {
+        let Some(key) = key else {
+            return Ok(Self::Text(value));
+        };
+        if value.is_empty() {
+            return Err(QueryError::MissingValue(key.to_owned()));
+        }
+
+        Ok(match key.to_lowercase().as_str() {
+            "front" => Self::Front(value),
+            "back" => Self::Back(value),
+            "deck" => Self::Deck(value),
+            "tag" => Self::Tag(value.trim_start_matches('#').to_owned()),
+            "due" => Self::Due(Comparison::parse(&value)?),
+            "interval" | "ivl" => Self::Interval(Comparison::parse(&value)?),
+            "is" => Self::Is(match value.to_lowercase().as_str() {
+                "new" => CardState::New,
+                "due" => CardState::Due,
+                "review" => CardState::Review,
+                "learn" | "learning" => CardState::Learning,
+                "suspended" => CardState::Suspended,
+                "buried" => CardState::Buried,
+                _ => return Err(QueryError::UnknownState(value)),
+            }),
+            _ => return Err(QueryError::UnknownKey(key.to_owned())),
+        })
+    }
+
+    fn matches(&self, path: &[&str], card: &Card, today: NaiveDate) -> bool {
+        match self {
+            Self::Text(pattern) => {
+                let pattern = format!("*{}*", pattern);
+                glob(&pattern, &card.front) || glob(&pattern, &card.back)
+            }
+            Self::Front(pattern) => glob(pattern, &card.front),
+            Self::Back(pattern) => glob(pattern, &card.back),
+            Self::Deck(pattern) => {
+                (1..=path.len()).any(|depth| glob(pattern, &path[..depth].join("::")))
+            }
+            Self::Tag(pattern) => card.tags.iter().any(|tag| glob(pattern, tag)),
+            Self::Due(comparison) => comparison.matches((card.due - today).num_days()),
+            Self::Interval(comparison) => comparison.matches(card.interval as i64),
+            Self::Is(CardState::New) => card.last_review.is_none(),
+            Self::Is(CardState::Due) => card.is_due(today),
+            Self::Is(CardState::Review) => card.last_review.is_some(),
+            Self::Is(CardState::Learning) => card.learning.is_some(),
+            Self::Is(CardState::Suspended) => card.suspended,
+            Self::Is(CardState::Buried) => card.is_buried(today),
+        }
+    }
+}
+
+/// Whether all of `text` matches `pattern`, ignoring case, where `*` matches any run of
+/// characters.
+fn glob(pattern: &str, text: &str) -> bool {
+    let pattern: Vec<char> = pattern.to_lowercase().chars().collect();
+    let text: Vec<char> = text.to_lowercase().chars().collect();
+
+    // matched[j] is whether the pattern so far matches the first j characters of the text
+    let mut matched = vec![false; text.len() + 1];
+    matched[0] = true;
+    for &p in &pattern {
+        let mut next = vec![false; text.len() + 1];
+        for j in 0..=text.len() {
+            next[j] = match p {
+                '*' => matched[j] || (j > 0 && next[j - 1]),
+                _ => j > 0 && matched[j - 1] && text[j - 1] == p,
+            };
+        }
+        matched = next;
+    }
+    matched[text.len()]
+}
+
+impl Query {
+    /// Parses a query of space-separated terms. A term is either text to look for, or a
+    /// `key:value` filter, and can be quoted to include spaces or negated with a leading `-`.
+    pub fn parse(query: &str) -> Result<Self, QueryError> {
+        let mut terms = Vec::new();
+        let mut chars = query.chars().peekable();
+        loop {
+            while chars.next_if(|c| c.is_whitespace()).is_some() {}
+            if chars.peek().is_none() {
+                break;
+            }
+
+            let negated = chars.next_if_eq(&'-').is_some();
+            let mut key = None;
+            let mut value = String::new();
+            let mut quoted = false;
+            while let Some(c) = chars.next_if(|c| quoted || !c.is_whitespace()) {
+                match c {
+                    '"' => quoted = !quoted,
+                    ':' if !quoted && key.is_none() => key = Some(std::mem::take(&mut value)),
+                    _ => value.push(c),
+                }
+            }
+            if quoted {
+                return Err(QueryError::UnclosedQuote);
+            }
+
+            terms.push(Term {
+                negated,
+                filter: Filter::parse(key.as_deref(), value)?,
+            });
+        }
+        Ok(Self { terms })
+    }
+
+    /// Whether `card`, in the deck at `path`, matches every term of the query.
+    pub fn matches(&self, path: &[&str], card: &Card, today: NaiveDate) -> bool {
+        self.terms
+            .iter()
+            .all(|term| term.filter.matches(path, card, today) != term.negated)
+    }
+}
+
+/// A card found by a search, along with the path of the deck it is in.
+pub struct SearchResult<'a> {
+    pub path: String,
+    pub card: &'a Card,
+}
+
+impl DeckNode {
+    /// Every card beneath this node matching `query`, with the names of the nodes leading to
+    /// its deck. The name of this node itself is left out of the paths.
+    pub fn search(&self, query: &Query, today: NaiveDate) -> Vec<SearchResult<'_>> {
+        fn walk<'a>(
+            node: &'a DeckNode,
+            path: &mut Vec<&'a str>,
+            query: &Query,
+            today: NaiveDate,
+            results: &mut Vec<SearchResult<'a>>,
+        ) {
+            match node {
+                DeckNode::Set { name, entries, .. } => {
+                    path.push(name);
+                    for entry in entries {
+                        walk(entry, path, query, today, results);
+                    }
+                    path.pop();
+                }
+                DeckNode::Deck { name, cards, .. } => {
+                    path.push(name);
+                    results.extend(
+                        cards
+                            .iter()
+                            .filter(|card| query.matches(path, card, today))
+                            .map(|card| SearchResult {
+                                path: path.join("::"),
+                                card,
+                            }),
+                    );
+                    path.pop();
+                }
+            }
+        }
+
+        let mut results = Vec::new();
+        match self {
+            Self::Set { entries, .. } => {
+                for entry in entries {
+                    walk(entry, &mut Vec::new(), query, today, &mut results);
+                }
+            }
+            Self::Deck { .. } => walk(self, &mut Vec::new(), query, today, &mut results),
+        }
+        results
+    }
+}
+
+const PATH_WIDTH: usize = 20;
+const SIDE_WIDTH: usize = 24;
+const DUE_WIDTH: usize = 10;
+const INTERVAL_WIDTH: usize = 8;
+
+/// A row of the browser's results table.
+struct Row {
+    id: Ulid,
+    text: String,
+}
+
+impl fmt::Display for Row {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.text)
+    }
+}
+
+fn row(path: &str, front: &str, back: &str, due: &str, interval: &str) -> String {
+    format!(
+        "{} {} {} {} {}",
+        column(path, PATH_WIDTH),
+        column(front, SIDE_WIDTH),
+        column(back, SIDE_WIDTH),
+        column(due, DUE_WIDTH),
+        column(interval, INTERVAL_WIDTH),
+    )
+}
+
+impl Collection {
+    /// Asks for a search query, lists the matching cards in a table, and applies an action to
+    /// the ones the user selects.
+    pub fn prompt_browse(&mut self) -> InquireResult<()> {
+        enum Action {
+            Move,
+            AddTags,
+            RemoveTags,
+            Suspend,
+            Unsuspend,
+            Reschedule,
+            Delete,
+            Back,
+        }
+
+        impl fmt::Display for Action {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                write!(
+                    f,
+                    "{}",
+                    match self {
+                        Self::Move => MOVE_CARDS,
+                        Self::AddTags => ADD_TAGS,
+                        Self::RemoveTags => REMOVE_TAGS,
+                        Self::Suspend => SUSPEND,
+                        Self::Unsuspend => UNSUSPEND,
+                        Self::Reschedule => RESCHEDULE,
+                        Self::Delete => REMOVE_CARDS,
+                        Self::Back => BACK,
+                    }
+                )
+            }
+        }
+
+        let text = Text::new(ENTER_QUERY)
+            .with_help_message(HELP_QUERY)
+            .with_validator(|text: &str| {
+                Ok(match Query::parse(text) {
+                    Ok(_) => Validation::Valid,
+                    Err(err) => Validation::Invalid(err.to_string().into()),
+                })
+            })
+            .prompt()?;
+        let Ok(query) = Query::parse(&text) else {
+            return Ok(());
+        };
+
+        let today = self.today();
+        let rows: Vec<Row> = self
+            .root
+            .search(&query, today)
+            .into_iter()
+            .map(|result| Row {
+                id: result.card.id,
+                text: row(
+                    &result.path,
+                    &result.card.question(),
+                    &result.card.answer(),
+                    &result.card.due.to_string(),
+                    &format!("{}d", result.card.interval),
+                ),
+            })
+            .collect();
+        if rows.is_empty() {
+            println!("{}", NO_MATCHES);
+            return Ok(());
+        }
+
+        println!("      {}", row("Deck", "Front", "Back", "Due", "Interval"));
+        let selected = MultiSelect::new(&format!("{} ({})", ENTER_SELECT_CARDS, rows.len()), rows)
+            .with_page_size(15)
+            .prompt()?;
+        let ids: Vec<Ulid> = selected.into_iter().map(|row| row.id).collect();
+        if ids.is_empty() {
+            return Ok(());
+        }
+
+        let options = vec![
+            Action::Move,
+            Action::AddTags,
+            Action::RemoveTags,
+            Action::Suspend,
+            Action::Unsuspend,
+            Action::Reschedule,
+            Action::Delete,
+            Action::Back,
+        ];
+        let action = Select::new(ENTER_BULK_ACTION, options).prompt()?;
+        match action {
+            Action::Move => return self.prompt_move_cards(&ids),
+            Action::AddTags => {
+                let tags = prompt_tags(&Default::default())?;
+                for id in &ids {
+                    if let Some(card) = self.root.find_card_mut(*id) {
+                        card.tags.extend(tags.iter().cloned());
+                    }
+                    self.root.sync_note(*id, &self.note_types, today);
+                }
+            }
+            Action::RemoveTags => {
+                let tags = prompt_tags(&Default::default())?;
+                for id in &ids {
+                    if let Some(card) = self.root.find_card_mut(*id) {
+                        card.tags.retain(|tag| !tags.contains(tag));
+                    }
+                    self.root.sync_note(*id, &self.note_types, today);
+                }
+            }
+            Action::Suspend | Action::Unsuspend => {
+                let suspended = matches!(action, Action::Suspend);
+                for id in &ids {
+                    if let Some(card) = self.root.find_card_mut(*id) {
+                        card.suspended = suspended;
+                    }
+                }
+            }
+            Action::Reschedule => {
+                let due = DateSelect::new(ENTER_DUE).with_default(today).prompt()?;
+                for id in &ids {
+                    if let Some(card) = self.root.find_card_mut(*id) {
+                        card.set_due(due);
+                    }
+                }
+            }
+            Action::Delete => {
+                if !prompt_confirm()? {
+                    return Ok(());
+                }
+
+                self.root.remove_cards(&ids);
+            }
+            Action::Back => return Ok(()),
+        }
+        self.save()?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn today() -> NaiveDate {
+        NaiveDate::from_ymd_opt(2024, 3, 1).unwrap()
+    }
+
+    fn card(front: &str, back: &str, due_in: i64, tags: &[&str]) -> Card {
+        let mut card = Card::new(front, back, today() + chrono::Duration::days(due_in));
+        card.tags = tags.iter().map(|tag| tag.to_string()).collect();
+        card
+    }
+
+    #[test]
+    fn globs_ignore_case_and_match_any_run_with_stars() {
+        assert!(glob("*ir", "gravir"));
+        assert!(glob("*IR", "Gravir"));
+        assert!(glob("gr*v*r", "gravir"));
+        assert!(glob("*", ""));
+        assert!(!glob("*ir", "manger"));
+        assert!(!glob("grav", "gravir"));
+    }
+
+    #[test]
+    fn matches_every_term_of_a_query() {
+        let query = Query::parse(
+            r#"deck:French::Vocab tag:verb due:<3 front:*ir "to climb" -is:suspended"#,
+        )
+        .unwrap();
+        let path = ["French", "Vocab"];
+
+        let climb = card("gravir", "to climb", 1, &["verb"]);
+        assert!(query.matches(&path, &climb, today()));
+
+        let mut suspended = climb.clone();
+        suspended.suspended = true;
+        assert!(!query.matches(&path, &suspended, today()));
+
+        let later = card("gravir", "to climb", 3, &["verb"]);
+        assert!(!query.matches(&path, &later, today()));
+
+        assert!(!query.matches(&["French", "Nouns"], &climb, today()));
+        assert!(!query.matches(&path, &card("voir", "to see", 0, &["verb"]), today()));
+    }
+
+    #[test]
+    fn deck_filters_include_decks_beneath() {
+        let query = Query::parse("deck:French").unwrap();
+        let card = card("manger", "to eat", 0, &[]);
+        assert!(query.matches(&["French", "Vocab"], &card, today()));
+        assert!(!query.matches(&["Comp Sci"], &card, today()));
+    }
+
+    #[test]
+    fn rejects_malformed_queries() {
+        assert_eq!(
+            Query::parse(r#""to climb"#).unwrap_err(),
+            QueryError::UnclosedQuote
+        );
+        assert_eq!(
+            Query::parse("colour:red").unwrap_err(),
+            QueryError::UnknownKey("colour".into())
+        );
+        assert_eq!(
+            Query::parse("tag:").unwrap_err(),
+            QueryError::MissingValue("tag".into())
+        );
+        assert_eq!(
+            Query::parse("due:<soon").unwrap_err(),
+            QueryError::InvalidNumber("soon".into())
+        );
+        assert_eq!(
+            Query::parse("is:lost").unwrap_err(),
+            QueryError::UnknownState("lost".into())
+        );
+    }
+}
@@ -1,38 +1,539 @@
-use inquire::Text;
+use std::{collections::BTreeSet, fmt};
+
+use chrono::Duration;
+use inquire::{validator::Validation, DateSelect, Select, Text};
+use serde::{Deserialize, Serialize};
+use ulid::Ulid;
 
 use crate::prelude::*;
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct Card {
+    #[serde(default = "Ulid::new")]
+    pub id: Ulid,
     pub front: String,
     pub back: String,
+    /// Which way round the card is asked.
+    #[serde(default)]
+    pub kind: CardKind,
+    /// The id shared by cards generated from the same note, such as the two directions of a
+    /// bidirectional card. Edits to one of them are applied to all of them.
+    #[serde(default)]
+    pub note: Option<Ulid>,
+    /// The values of the note's fields, for cards generated from a note type.
+    #[serde(default)]
+    pub fields: Vec<String>,
+    #[serde(default)]
+    pub tags: BTreeSet<String>,
+    /// Whether the card is left out of study until it is unsuspended.
+    #[serde(default)]
+    pub suspended: bool,
+    /// The day until which the card is left out of study, if it was buried.
+    #[serde(default)]
+    pub buried_until: Option<NaiveDate>,
     pub due: NaiveDate,
+    /// The moment the card is due while it is in a learning step, which may be later on the
+    /// day it is `due`.
+    #[serde(default)]
+    pub due_at: Option<DateTime<Utc>>,
+    /// The learning or relearning step the card is at, if it is in one.
+    #[serde(default)]
+    pub learning: Option<LearningStep>,
+    /// How much the interval grows by after each successful review.
+    #[serde(default = "default_ease")]
+    pub ease: f64,
+    /// The number of days between the last review and `due`.
+    #[serde(default)]
+    pub interval: u32,
+    /// The number of successful reviews in a row since the card was new or last forgotten.
+    #[serde(default)]
+    pub reps: u32,
+    /// The number of times the card was forgotten after having been learned.
+    #[serde(default)]
+    pub lapses: u32,
+    /// The FSRS model of the card's memory, once it has been reviewed under FSRS.
+    #[serde(default)]
+    pub memory: Option<MemoryState>,
+    /// The index of the Leitner box the card is in.
+    #[serde(default)]
+    pub leitner_box: usize,
+    #[serde(default)]
+    pub last_review: Option<NaiveDate>,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum CardKind {
+    /// Shows the front and asks for the back, with no linked cards.
+    #[default]
+    Basic,
+    /// The front-to-back half of a bidirectional pair.
+    Forward,
+    /// The back-to-front half of a bidirectional pair.
+    Reverse,
+    /// Asks for the deletions with the given number in the cloze text on the front, with
+    /// the back holding any extra information shown with the answer.
+    Cloze(u32),
+    /// Generated from a note by the given template of a note type, with the front and back
+    /// rendered from the card's field values.
+    Template { note_type: Ulid, template: Ulid },
+}
+
+/// What the user chose to do with a card shown during review.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Answer {
+    Grade(Grade),
+    /// Hide the card until tomorrow without grading it.
+    Bury,
+    /// Hide the card until it is unsuspended, without grading it.
+    Suspend,
+}
+
+impl Answer {
+    /// Every answer, with the grades first.
+    pub fn all() -> Vec<Self> {
+        let mut answers: Vec<Self> = Grade::ALL.into_iter().map(Self::Grade).collect();
+        answers.push(Self::Bury);
+        answers.push(Self::Suspend);
+        answers
+    }
+}
+
+impl fmt::Display for Answer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Grade(grade) => grade.fmt(f),
+            Self::Bury => write!(f, "{}", BURY),
+            Self::Suspend => write!(f, "{}", SUSPEND),
+        }
+    }
+}
+
+/// A single past review of a card, on the study day it happened.
+#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
+pub struct Review {
+    pub date: NaiveDate,
+    pub grade: Grade,
+}
+
+fn default_ease() -> f64 {
+    SM2_INITIAL_EASE
 }
 
 impl Card {
-    pub fn new(front: impl Into<String>, back: impl Into<String>) -> Self {
+    /// A new card, due right away on `today`.
+    pub fn new(front: impl Into<String>, back: impl Into<String>, today: NaiveDate) -> Self {
         Self {
+            id: Ulid::new(),
             front: front.into(),
             back: back.into(),
-            due: Utc::now().date_naive(),
+            kind: CardKind::Basic,
+            note: None,
+            fields: Vec::new(),
+            tags: BTreeSet::new(),
+            suspended: false,
+            buried_until: None,
+            due: today,
+            due_at: None,
+            learning: None,
+            ease: SM2_INITIAL_EASE,
+            interval: 0,
+            reps: 0,
+            lapses: 0,
+            memory: None,
+            leitner_box: 0,
+            last_review: None,
+        }
+    }
+
+    /// A linked pair of cards, one asking for the back given the front and the other asking
+    /// for the front given the back, each scheduled on its own.
+    pub fn bidirectional(
+        front: impl Into<String>,
+        back: impl Into<String>,
+        today: NaiveDate,
+    ) -> [Self; 2] {
+        let note = Some(Ulid::new());
+        let forward = Self {
+            kind: CardKind::Forward,
+            note,
+            ..Self::new(front, back, today)
+        };
+        let reverse = Self {
+            id: Ulid::new(),
+            kind: CardKind::Reverse,
+            ..forward.clone()
+        };
+        [forward, reverse]
+    }
+
+    /// One card for every deletion number in the cloze `text`.
+    pub fn cloze(text: impl Into<String>, extra: impl Into<String>, today: NaiveDate) -> Vec<Self> {
+        let note = Some(Ulid::new());
+        let template = Self::new(text, extra, today);
+        cloze_numbers(&template.front)
+            .into_iter()
+            .map(|number| Self {
+                id: Ulid::new(),
+                kind: CardKind::Cloze(number),
+                note,
+                ..template.clone()
+            })
+            .collect()
+    }
+
+    /// Asks for the contents and tags of a new card, returning every card generated from it.
+    /// Notes of any of the `note_types` can be added as well as the built-in kinds.
+    pub fn prompt_new(note_types: &[NoteType], today: NaiveDate) -> InquireResult<Vec<Self>> {
+        let mut cards = Self::prompt_contents(note_types, today)?;
+        let tags = prompt_tags(&BTreeSet::new())?;
+        for card in &mut cards {
+            card.tags = tags.clone();
         }
+        Ok(cards)
     }
 
-    pub fn prompt_new() -> InquireResult<Self> {
+    fn prompt_contents(note_types: &[NoteType], today: NaiveDate) -> InquireResult<Vec<Self>> {
+        let mut kinds = vec![
+            CARD_BASIC.to_owned(),
+            CARD_BIDIRECTIONAL.to_owned(),
+            CARD_CLOZE.to_owned(),
+        ];
+        let built_in = kinds.len();
+        kinds.extend(note_types.iter().map(|note_type| note_type.name.clone()));
+        let choice = Select::new(ENTER_CARD_TYPE, kinds).raw_prompt()?;
+        let kind = choice.value.as_str();
+
+        if let Some(note_type) = choice.index.checked_sub(built_in).map(|i| &note_types[i]) {
+            let values = note_type.prompt_values(&[])?;
+            return Ok(note_type.generate(values, today));
+        }
+
+        if kind == CARD_CLOZE {
+            let text = Text::new(ENTER_CLOZE_TEXT)
+                .with_help_message(HELP_CLOZE_TEXT)
+                .with_validator(|text: &str| {
+                    Ok(if cloze_numbers(text).is_empty() {
+                        Validation::Invalid(INVALID_CLOZE_TEXT.into())
+                    } else {
+                        Validation::Valid
+                    })
+                })
+                .prompt()?;
+            let extra = Text::new(ENTER_CLOZE_EXTRA)
+                .with_help_message(HELP_CLOZE_EXTRA)
+                .prompt()?;
+            return Ok(Self::cloze(text, extra, today));
+        }
+
+        let bidirectional = kind == CARD_BIDIRECTIONAL;
         let front = Text::new(ENTER_CARD_FRONT)
             .with_help_message(HELP_CARD_FRONT)
             .prompt()?;
         let back = Text::new(ENTER_CARD_BACK)
             .with_help_message(HELP_CARD_BACK)
             .prompt()?;
-        Ok(Self::new(front, back))
+
+        Ok(if bidirectional {
+            Self::bidirectional(front, back, today).to_vec()
+        } else {
+            vec![Self::new(front, back, today)]
+        })
+    }
+
+    /// The text the user is asked about.
+    pub fn question(&self) -> String {
+        match self.kind {
+            CardKind::Basic | CardKind::Forward | CardKind::Template { .. } => self.front.clone(),
+            CardKind::Reverse => self.back.clone(),
+            CardKind::Cloze(number) => render_cloze(&self.front, Some(number)),
+        }
+    }
+
+    /// The text the user is trying to remember.
+    pub fn answer(&self) -> String {
+        match self.kind {
+            CardKind::Basic | CardKind::Forward | CardKind::Template { .. } => self.back.clone(),
+            CardKind::Reverse => self.front.clone(),
+            CardKind::Cloze(_) => {
+                let full = render_cloze(&self.front, None);
+                if self.back.is_empty() {
+                    full
+                } else {
+                    format!("{}\n\n{}", full, self.back)
+                }
+            }
+        }
+    }
+
+    /// What the user should type when typing in the answer: for cloze cards, only the
+    /// hidden deletions, and for cards from one of the `note_types`, the back without the
+    /// front it repeats through `{{FrontSide}}`.
+    pub fn expected_answer(&self, note_types: &[NoteType]) -> String {
+        match self.kind {
+            CardKind::Cloze(number) => clozes(&self.front)
+                .into_iter()
+                .filter(|cloze| cloze.number == number)
+                .map(|cloze| cloze.answer)
+                .collect::<Vec<_>>()
+                .join(", "),
+            _ => note_types
+                .iter()
+                .find_map(|note_type| note_type.answer(self))
+                .unwrap_or_else(|| self.answer()),
+        }
     }
 
     pub fn display_name(&self) -> String {
-        format!(
-            "🃏 {} / {}",
-            ellipsis(&self.front, TEXT_WIDTH),
-            ellipsis(&self.back, TEXT_WIDTH)
-        )
+        let name = match self.kind {
+            CardKind::Basic => format!(
+                "🃏 {} / {}",
+                ellipsis(&self.front, TEXT_WIDTH),
+                ellipsis(&self.back, TEXT_WIDTH)
+            ),
+            CardKind::Forward | CardKind::Reverse => format!(
+                "🔗 {} → {}",
+                ellipsis(&self.question(), TEXT_WIDTH),
+                ellipsis(&self.answer(), TEXT_WIDTH)
+            ),
+            CardKind::Cloze(number) => format!(
+                "🧩 c{}: {}",
+                number,
+                ellipsis(&self.question(), TEXT_WIDTH * 2)
+            ),
+            CardKind::Template { .. } => format!(
+                "📝 {} / {}",
+                ellipsis(&self.front, TEXT_WIDTH),
+                ellipsis(&self.back, TEXT_WIDTH)
+            ),
+        };
+        let name = if self.suspended {
+            format!("⏸️ {}", name)
+        } else {
+            name
+        };
+        if self.tags.is_empty() {
+            name
+        } else {
+            format!("{} {}", name, display_tags(&self.tags))
+        }
+    }
+
+    pub fn has_tag(&self, tag: &str) -> bool {
+        self.tags.contains(tag)
+    }
+
+    /// Copies the contents and tags of `other` into this card, if both were generated from the same
+    /// note, leaving the scheduling state alone. The leech tag belongs to the card that lapsed,
+    /// so it is left as it is. Cards generated from a note type only take the field values, and
+    /// need rendering again afterwards.
+    pub fn sync_from(&mut self, other: &Card) {
+        if self.note.is_none() || self.note != other.note {
+            return;
+        }
+        let leech = self.has_tag(LEECH_TAG);
+        self.tags = other.tags.clone();
+        if leech {
+            self.tags.insert(LEECH_TAG.to_owned());
+        } else {
+            self.tags.remove(LEECH_TAG);
+        }
+        match self.kind {
+            CardKind::Template { .. } => self.fields = other.fields.clone(),
+            _ => {
+                self.front = other.front.clone();
+                self.back = other.back.clone();
+            }
+        }
+    }
+
+    pub fn is_due(&self, today: NaiveDate) -> bool {
+        self.due <= today
+    }
+
+    pub fn is_buried(&self, today: NaiveDate) -> bool {
+        self.buried_until.is_some_and(|until| today < until)
+    }
+
+    /// Whether the card can be studied today, being neither suspended nor buried.
+    pub fn is_active(&self, today: NaiveDate) -> bool {
+        !self.suspended && !self.is_buried(today)
+    }
+
+    /// Moves the card to be due on `due`, taking it out of any learning step it is in.
+    pub fn set_due(&mut self, due: NaiveDate) {
+        self.due = due;
+        self.due_at = None;
+        self.learning = None;
+    }
+
+    /// Hides the card until the day after `today`.
+    pub fn bury(&mut self, today: NaiveDate) {
+        self.buried_until = Some(today + Duration::days(1));
+    }
+
+    /// Shows the front of the card, waits for the user to reveal the back, then asks how it went.
+    pub fn prompt_ask(&self) -> InquireResult<Answer> {
+        Text::new(&text_block(&self.question()))
+            .with_help_message(HELP_REVEAL)
+            .prompt()?;
+        Select::new(&text_block(&self.answer()), Answer::all())
+            .with_starting_cursor(2)
+            .prompt()
+    }
+
+    /// Reschedules the card according to how the review at `now` went, with days counted by
+    /// `day`.
+    pub fn review(&mut self, grade: Grade, now: DateTime<Utc>, day: &DaySettings, preset: &Preset) {
+        preset.review(self, grade, now, day);
+        self.last_review = Some(day.day_of(now));
+    }
+
+    /// Prints both sides of the card, along with when it is next due, in the timezone of
+    /// `day`.
+    pub fn preview(&self, day: &DaySettings) {
+        let due = match self.due_at {
+            Some(due_at) => format!(
+                "{} ({})",
+                day.local(due_at).format("%Y-%m-%d %H:%M"),
+                LEARNING
+            ),
+            None => format!("{} ({} days)", self.due, self.interval),
+        };
+        println!(
+            "{}{}\n    {} {}",
+            text_block(&self.question()),
+            text_block(&self.answer()),
+            DUE,
+            due
+        );
+    }
+
+    /// Shows the card editor, applying a single change to the card. Cards generated from one
+    /// of the `note_types` have their fields edited instead of their front and back, and days
+    /// are counted by `day`.
+    pub fn prompt_edit(
+        &mut self,
+        note_types: &[NoteType],
+        day: &DaySettings,
+    ) -> InquireResult<EditStep> {
+        enum Selection {
+            Preview,
+            EditFields,
+            EditFront,
+            EditBack,
+            EditTags,
+            EditDue,
+            Suspend,
+            Unsuspend,
+            Bury,
+            Unbury,
+            Back,
+        }
+
+        impl fmt::Display for Selection {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                write!(
+                    f,
+                    "{}",
+                    match self {
+                        Self::Preview => PREVIEW,
+                        Self::EditFields => EDIT_FIELDS,
+                        Self::EditFront => EDIT_FRONT,
+                        Self::EditBack => EDIT_BACK,
+                        Self::EditTags => EDIT_TAGS,
+                        Self::EditDue => EDIT_DUE,
+                        Self::Suspend => SUSPEND,
+                        Self::Unsuspend => UNSUSPEND,
+                        Self::Bury => BURY,
+                        Self::Unbury => UNBURY,
+                        Self::Back => BACK,
+                    }
+                )
+            }
+        }
+
+        let note_type = match self.kind {
+            CardKind::Template { note_type, .. } => note_types
+                .iter()
+                .find(|candidate| candidate.id == note_type),
+            _ => None,
+        };
+
+        let today = day.today();
+        let mut options = match note_type {
+            Some(_) => vec![
+                Selection::Preview,
+                Selection::EditFields,
+                Selection::EditTags,
+                Selection::EditDue,
+            ],
+            None => vec![
+                Selection::Preview,
+                Selection::EditFront,
+                Selection::EditBack,
+                Selection::EditTags,
+                Selection::EditDue,
+            ],
+        };
+        options.push(match self.suspended {
+            true => Selection::Unsuspend,
+            false => Selection::Suspend,
+        });
+        options.push(match self.is_buried(today) {
+            true => Selection::Unbury,
+            false => Selection::Bury,
+        });
+        options.push(Selection::Back);
+
+        match Select::new(&self.display_name(), options).prompt()? {
+            Selection::Preview => {
+                self.preview(day);
+                Ok(EditStep::Unchanged)
+            }
+            Selection::EditFields => {
+                let Some(note_type) = note_type else {
+                    return Ok(EditStep::Unchanged);
+                };
+                self.fields = note_type.prompt_values(&self.fields)?;
+                note_type.render(self);
+                Ok(EditStep::Changed)
+            }
+            Selection::EditFront => {
+                self.front = Text::new(&format!("{} ->", ellipsis(&self.front, TEXT_WIDTH)))
+                    .with_initial_value(&self.front)
+                    .prompt()?;
+                Ok(EditStep::Changed)
+            }
+            Selection::EditBack => {
+                self.back = Text::new(&format!("{} ->", ellipsis(&self.back, TEXT_WIDTH)))
+                    .with_initial_value(&self.back)
+                    .prompt()?;
+                Ok(EditStep::Changed)
+            }
+            Selection::EditTags => {
+                self.tags = prompt_tags(&self.tags)?;
+                Ok(EditStep::Changed)
+            }
+            Selection::EditDue => {
+                let due = DateSelect::new(&format!("{} ->", self.due))
+                    .with_default(self.due)
+                    .prompt()?;
+                self.set_due(due);
+                Ok(EditStep::Changed)
+            }
+            Selection::Suspend | Selection::Unsuspend => {
+                self.suspended = !self.suspended;
+                Ok(EditStep::Changed)
+            }
+            Selection::Bury => {
+                self.bury(today);
+                Ok(EditStep::Changed)
+            }
+            Selection::Unbury => {
+                self.buried_until = None;
+                Ok(EditStep::Changed)
+            }
+            Selection::Back => Ok(EditStep::Back),
+        }
     }
 }
@@ -0,0 +1,140 @@
+/// A cloze deletion in a card's text: a part of it hidden while asking the card, written as
+/// `{{c1::answer}}`, or `{{c1::answer::hint}}` to show a hint in its place.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Cloze {
+    pub number: u32,
+    pub answer: String,
+    pub hint: Option<String>,
+}
+
+/// A piece of cloze text: either plain text, or a deletion.
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum Segment<'a> {
+    Text(&'a str),
+    Cloze(Cloze),
+}
+
+/// Splits `text` into plain text and deletions. Markers that aren't well-formed are kept
+/// as plain text.
+fn parse(text: &str) -> Vec<Segment<'_>> {
+    let mut segments = Vec::new();
+    let mut rest = text;
+
+    while let Some(start) = rest.find("{{c") {
+        let after = &rest[start + 3..];
+        let digits = after.chars().take_while(char::is_ascii_digit).count();
+        let body = &after[digits..];
+
+        let parsed = (digits > 0 && body.starts_with("::"))
+            .then(|| body[2..].find("}}").map(|end| (&body[2..2 + end], end)))
+            .flatten();
+        let Some((inner, end)) = parsed else {
+            segments.push(Segment::Text(&rest[..start + 3]));
+            rest = after;
+            continue;
+        };
+
+        let mut parts = inner.splitn(2, "::");
+        let answer = parts.next().unwrap_or_default().to_owned();
+        let hint = parts.next().map(str::to_owned);
+
+        segments.push(Segment::Text(&rest[..start]));
+        segments.push(Segment::Cloze(Cloze {
+            number: after[..digits].parse().unwrap_or(0),
+            answer,
+            hint,
+        }));
+        rest = &body[2 + end + 2..];
+    }
+
+    segments.push(Segment::Text(rest));
+    segments
+}
+
+/// Every deletion in `text`, in order.
+pub fn clozes(text: &str) -> Vec<Cloze> {
+    parse(text)
+        .into_iter()
+        .filter_map(|segment| match segment {
+            Segment::Cloze(cloze) => Some(cloze),
+            Segment::Text(_) => None,
+        })
+        .collect()
+}
+
+/// The distinct deletion numbers used in `text`, each of which becomes its own card.
+pub fn cloze_numbers(text: &str) -> Vec<u32> {
+    let mut numbers: Vec<u32> = clozes(text).iter().map(|cloze| cloze.number).collect();
+    numbers.sort_unstable();
+    numbers.dedup();
+    numbers
+}
+
+/// Renders `text` with every deletion numbered `hidden` replaced by `[...]`, or by its hint
+/// in brackets, and every other deletion revealed.
+pub fn render_cloze(text: &str, hidden: Option<u32>) -> String {
+    parse(text)
+        .into_iter()
+        .map(|segment| match segment {
+            Segment::Text(text) => text.to_owned(),
+            Segment::Cloze(cloze) if Some(cloze.number) == hidden => {
+                format!("[{}]", cloze.hint.as_deref().unwrap_or("..."))
+            }
+            Segment::Cloze(cloze) => cloze.answer,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TEXT: &str = "{{c1::Paris::city}} is the capital of {{c2::France}}";
+
+    #[test]
+    fn parses_answers_and_hints() {
+        assert_eq!(
+            parse("{{c1::answer::hint}}!"),
+            vec![
+                Segment::Text(""),
+                Segment::Cloze(Cloze {
+                    number: 1,
+                    answer: "answer".into(),
+                    hint: Some("hint".into()),
+                }),
+                Segment::Text("!"),
+            ]
+        );
+        assert_eq!(
+            clozes(TEXT)[1],
+            Cloze {
+                number: 2,
+                answer: "France".into(),
+                hint: None,
+            }
+        );
+    }
+
+    #[test]
+    fn keeps_malformed_markers_as_text() {
+        for text in ["{{c::x}}", "{{c1:x}}", "{{c1::x", "{{cx::y}}"] {
+            assert!(clozes(text).is_empty(), "{}", text);
+            assert_eq!(render_cloze(text, Some(1)), text);
+        }
+    }
+
+    #[test]
+    fn numbers_are_distinct_and_sorted() {
+        assert_eq!(cloze_numbers("{{c2::a}} {{c1::b}} {{c2::c}}"), vec![1, 2]);
+    }
+
+    #[test]
+    fn renders_the_hidden_deletion_as_a_blank_or_its_hint() {
+        assert_eq!(
+            render_cloze(TEXT, Some(1)),
+            "[city] is the capital of France"
+        );
+        assert_eq!(render_cloze(TEXT, Some(2)), "Paris is the capital of [...]");
+        assert_eq!(render_cloze(TEXT, None), "Paris is the capital of France");
+    }
+}
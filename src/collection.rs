@@ -0,0 +1,226 @@
+use std::{
+    fs,
+    io::{self, Write},
+    path::{Path, PathBuf},
+};
+
+use serde::{Deserialize, Serialize};
+use ulid::Ulid;
+
+use crate::prelude::*;
+
+const DATA_DIR_VAR: &str = "SRS_DATA_DIR";
+const COLLECTION_FILE: &str = "collection.json";
+
+/// The user's whole deck tree, as stored on disk.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Collection {
+    pub root: DeckNode,
+    #[serde(default)]
+    pub note_types: Vec<NoteType>,
+    /// The options presets decks can use. The first is the default, used by every node
+    /// without a preset of its own or inherited from a set.
+    #[serde(default = "default_presets")]
+    pub presets: Vec<Preset>,
+    /// When each study day starts.
+    #[serde(default)]
+    pub day: DaySettings,
+    #[serde(skip)]
+    path: PathBuf,
+    /// Every review in the review log, loaded along with the collection.
+    #[serde(skip)]
+    pub(crate) history: Vec<ReviewLogEntry>,
+}
+
+impl Collection {
+    pub fn new(root: DeckNode, path: impl Into<PathBuf>) -> Self {
+        Self {
+            root,
+            note_types: Vec::new(),
+            presets: default_presets(),
+            day: DaySettings::default(),
+            path: path.into(),
+            history: Vec::new(),
+        }
+    }
+
+    /// Loads the collection stored at `path`, or returns `None` if there is no file there yet.
+    pub fn load(path: impl Into<PathBuf>) -> io::Result<Option<Self>> {
+        let path = path.into();
+        let bytes = match fs::read(&path) {
+            Ok(bytes) => bytes,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(None),
+            Err(err) => return Err(err),
+        };
+
+        let mut collection: Self = serde_json::from_slice(&bytes)?;
+        collection.path = path;
+        if collection.presets.is_empty() {
+            collection.presets = default_presets();
+        }
+        collection.history = collection.review_log().load()?;
+        Ok(Some(collection))
+    }
+
+    /// Writes the collection to a temporary file next to its path, then renames it over the
+    /// old one, so a crash mid-write never leaves a truncated collection behind.
+    pub fn save(&self) -> io::Result<()> {
+        if let Some(dir) = self.path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+
+        let tmp_path = self.path.with_extension("json.tmp");
+        let mut file = fs::File::create(&tmp_path)?;
+        serde_json::to_writer_pretty(&mut file, self)?;
+        file.write_all(b"\n")?;
+        file.sync_all()?;
+        drop(file);
+
+        fs::rename(&tmp_path, &self.path)
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn prompt_select(&mut self, path: DeckPath, action: NodeAction) -> InquireResult<()> {
+        match action {
+            NodeAction::ToggleExpanded => {
+                let Some(DeckNode::Set { expanded, .. }) = self.root.at_mut(path) else {
+                    // The set has been removed since the menu was shown
+                    return Ok(());
+                };
+                *expanded = !*expanded;
+                self.save()?;
+                Ok(())
+            }
+            NodeAction::Edit => self.prompt_edit(path),
+        }
+    }
+
+    /// Runs the edit menu of the node at `path`, saving after every change made through it.
+    pub fn prompt_edit(&mut self, path: DeckPath) -> InquireResult<()> {
+        loop {
+            let Some(id) = self.root.at(path.clone()).map(DeckNode::id) else {
+                return Ok(());
+            };
+            let preset = self.preset_for(id).clone();
+            let today = self.today();
+            let Some(target) = self.root.at_mut(path.clone()) else {
+                return Ok(());
+            };
+
+            match target.prompt_edit(&self.note_types, &preset, today)? {
+                EditStep::Changed => self.save()?,
+                EditStep::Unchanged => {}
+                EditStep::Study => self.prompt_study(path.clone())?,
+                EditStep::Optimize => {
+                    if self.prompt_optimize(id)? {
+                        self.save()?;
+                    }
+                }
+                EditStep::Options => self.prompt_node_options(path.clone())?,
+                EditStep::EditCard(id) => self.prompt_edit_card(id)?,
+                EditStep::MoveNode(id) => self.prompt_move_node(id)?,
+                EditStep::MoveCards(ids) => self.prompt_move_cards(&ids)?,
+                EditStep::RemoveCards(ids) => {
+                    self.root.remove_cards(&ids);
+                    self.save()?;
+                }
+                EditStep::Back => return Ok(()),
+            }
+        }
+    }
+
+    /// Asks which set to move the node with the given id into, then moves it there. Sets
+    /// within the node itself are not offered, since it can't be moved into itself.
+    pub fn prompt_move_node(&mut self, id: Ulid) -> InquireResult<()> {
+        let Some(node) = self.root.find(id) else {
+            return Ok(());
+        };
+
+        let destination = self.root.prompt_node_matching(ENTER_MOVE_TO, |target| {
+            matches!(target, DeckNode::Set { entries, .. }
+                if node.find(target.id()).is_none()
+                    && entries.iter().all(|entry| entry.id() != id))
+        })?;
+        let Some(destination) = destination else {
+            println!("{}", NO_MOVE_DESTINATION);
+            return Ok(());
+        };
+
+        let Some(to) = self.root.at(destination).map(DeckNode::id) else {
+            return Ok(());
+        };
+        if !self.root.move_node(id, to) {
+            println!("{}", INVALID_MOVE);
+            return Ok(());
+        }
+        self.save()?;
+        Ok(())
+    }
+
+    /// Asks which deck to move the cards with the given ids into, then moves them there.
+    pub fn prompt_move_cards(&mut self, ids: &[Ulid]) -> InquireResult<()> {
+        let Some(from) = ids
+            .first()
+            .and_then(|id| self.root.deck_of_mut(*id))
+            .map(|deck| deck.id())
+        else {
+            return Ok(());
+        };
+
+        let destination = self.root.prompt_node_matching(ENTER_MOVE_TO, |target| {
+            matches!(target, DeckNode::Deck { .. }) && target.id() != from
+        })?;
+        let Some(destination) = destination else {
+            println!("{}", NO_MOVE_DESTINATION);
+            return Ok(());
+        };
+
+        let Some(to) = self.root.at(destination).map(DeckNode::id) else {
+            return Ok(());
+        };
+        if self.root.move_cards(ids, to) > 0 {
+            self.save()?;
+        }
+        Ok(())
+    }
+
+    /// Runs the editor of the card with the given id, saving after every change made in it.
+    pub fn prompt_edit_card(&mut self, id: Ulid) -> InquireResult<()> {
+        loop {
+            let Some(card) = self.root.find_card_mut(id) else {
+                return Ok(());
+            };
+
+            match card.prompt_edit(&self.note_types, &self.day)? {
+                EditStep::Changed => {
+                    self.root.sync_note(id, &self.note_types, self.day.today());
+                    self.save()?;
+                }
+                EditStep::Back => return Ok(()),
+                _ => {}
+            }
+        }
+    }
+}
+
+fn default_presets() -> Vec<Preset> {
+    vec![Preset::default()]
+}
+
+/// The per-user directory the collection lives in, overridable through `SRS_DATA_DIR`.
+pub fn data_dir() -> io::Result<PathBuf> {
+    if let Some(dir) = std::env::var_os(DATA_DIR_VAR) {
+        return Ok(PathBuf::from(dir));
+    }
+
+    dirs::data_dir()
+        .map(|dir| dir.join("srs"))
+        .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "no data directory for this user"))
+}
+
+pub fn collection_path() -> io::Result<PathBuf> {
+    Ok(data_dir()?.join(COLLECTION_FILE))
+}
@@ -0,0 +1,229 @@
+use std::fmt;
+
+use chrono::Duration;
+use inquire::{validator::Validation, CustomType, Select, Text};
+use serde::{Deserialize, Serialize};
+
+use crate::prelude::*;
+
+pub const DAY_DEFAULT_ROLLOVER_HOUR: u32 = 4;
+/// The furthest any timezone is from UTC, in minutes.
+const MAXIMUM_UTC_OFFSET: i32 = 14 * 60;
+
+/// Where one study day ends and the next begins. Due dates, daily limits, statistics and
+/// streaks all count days this way, rather than by midnight in UTC.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DaySettings {
+    /// The offset from UTC, in minutes, of the timezone days are counted in, or `None` to
+    /// use the system's local timezone.
+    #[serde(default)]
+    pub utc_offset: Option<i32>,
+    /// The hour, in that timezone, at which the next day starts.
+    #[serde(default = "default_rollover_hour")]
+    pub rollover_hour: u32,
+}
+
+impl Default for DaySettings {
+    fn default() -> Self {
+        Self {
+            utc_offset: None,
+            rollover_hour: DAY_DEFAULT_ROLLOVER_HOUR,
+        }
+    }
+}
+
+fn default_rollover_hour() -> u32 {
+    DAY_DEFAULT_ROLLOVER_HOUR
+}
+
+impl DaySettings {
+    /// The offset from UTC of the timezone at `time`.
+    pub fn offset(&self, time: DateTime<Utc>) -> FixedOffset {
+        match self
+            .utc_offset
+            .and_then(|minutes| FixedOffset::east_opt(minutes * 60))
+        {
+            Some(offset) => offset,
+            None => *time.with_timezone(&Local).offset(),
+        }
+    }
+
+    /// `time` as shown on a clock in the timezone.
+    pub fn local(&self, time: DateTime<Utc>) -> DateTime<FixedOffset> {
+        time.with_timezone(&self.offset(time))
+    }
+
+    /// The study day `time` falls on. Times before the rollover hour still count towards
+    /// the day before.
+    pub fn day_of(&self, time: DateTime<Utc>) -> NaiveDate {
+        (self.local(time) - Duration::hours(self.rollover_hour as i64)).date_naive()
+    }
+
+    pub fn today(&self) -> NaiveDate {
+        self.day_of(Utc::now())
+    }
+
+    pub fn prompt_edit(&mut self) -> InquireResult<EditStep> {
+        enum Selection {
+            Timezone,
+            RolloverHour,
+            Back,
+        }
+
+        impl fmt::Display for Selection {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                write!(
+                    f,
+                    "{}",
+                    match self {
+                        Self::Timezone => TIMEZONE,
+                        Self::RolloverHour => ROLLOVER_HOUR,
+                        Self::Back => BACK,
+                    }
+                )
+            }
+        }
+
+        let options = vec![
+            Selection::Timezone,
+            Selection::RolloverHour,
+            Selection::Back,
+        ];
+        match Select::new(&self.to_string(), options).prompt()? {
+            Selection::Timezone => {
+                let current = self.utc_offset.map(format_utc_offset).unwrap_or_default();
+                let input = Text::new(ENTER_TIMEZONE)
+                    .with_initial_value(&current)
+                    .with_help_message(HELP_TIMEZONE)
+                    .with_validator(|input: &str| {
+                        Ok(match parse_utc_offset(input) {
+                            Some(_) => Validation::Valid,
+                            None => Validation::Invalid(INVALID_TIMEZONE.into()),
+                        })
+                    })
+                    .prompt()?;
+                self.utc_offset = parse_utc_offset(&input).unwrap_or_default();
+            }
+            Selection::RolloverHour => {
+                self.rollover_hour = CustomType::new(ENTER_ROLLOVER_HOUR)
+                    .with_default(self.rollover_hour)
+                    .with_help_message(HELP_ROLLOVER_HOUR)
+                    .with_validator(|hour: &u32| {
+                        Ok(if *hour < 24 {
+                            Validation::Valid
+                        } else {
+                            Validation::Invalid(INVALID_ROLLOVER_HOUR.into())
+                        })
+                    })
+                    .prompt()?;
+            }
+            Selection::Back => return Ok(EditStep::Back),
+        }
+        Ok(EditStep::Changed)
+    }
+}
+
+impl fmt::Display for DaySettings {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let timezone = match self.utc_offset {
+            Some(minutes) => format_utc_offset(minutes),
+            None => format!(
+                "{} ({})",
+                format_utc_offset(self.offset(Utc::now()).local_minus_utc() / 60),
+                SYSTEM_TIMEZONE
+            ),
+        };
+
+        writeln!(f, "⚙️ {}", SETTINGS)?;
+        writeln!(f, "  Timezone            {}", timezone)?;
+        write!(f, "  Next day starts at  {:02}:00", self.rollover_hour)
+    }
+}
+
+/// Formats an offset from UTC in minutes, such as `-480` as `UTC-08:00`.
+fn format_utc_offset(minutes: i32) -> String {
+    let sign = if minutes < 0 { '-' } else { '+' };
+    let minutes = minutes.abs();
+    format!("UTC{}{:02}:{:02}", sign, minutes / 60, minutes % 60)
+}
+
+/// Parses an offset from UTC such as `UTC`, `UTC-8`, `+05:30` or `+0100` into minutes, with
+/// an empty input or `local` meaning the system's timezone.
+fn parse_utc_offset(input: &str) -> Option<Option<i32>> {
+    let input = input.trim();
+    if input.is_empty() || input.eq_ignore_ascii_case("local") {
+        return Some(None);
+    }
+
+    let input = input
+        .strip_prefix("UTC")
+        .or_else(|| input.strip_prefix("utc"))
+        .unwrap_or(input);
+    if input.is_empty() {
+        return Some(Some(0));
+    }
+    if !input.is_ascii() {
+        return None;
+    }
+    let (sign, rest) = match input.chars().next()? {
+        '+' => (1, &input[1..]),
+        '-' => (-1, &input[1..]),
+        _ => return None,
+    };
+    let (hours, minutes) = match rest.split_once(':') {
+        Some((hours, minutes)) => (hours, minutes),
+        None if rest.len() == 4 => rest.split_at(2),
+        None => (rest, "0"),
+    };
+    let hours: u32 = hours.parse().ok()?;
+    let minutes: u32 = minutes.parse().ok()?;
+    if hours > MAXIMUM_UTC_OFFSET as u32 / 60 || minutes >= 60 {
+        return None;
+    }
+
+    let offset = sign * (hours * 60 + minutes) as i32;
+    (offset.abs() <= MAXIMUM_UTC_OFFSET).then_some(Some(offset))
+}
+
+impl Collection {
+    /// The study day it is now.
+    pub fn today(&self) -> NaiveDate {
+        self.day.today()
+    }
+
+    /// Runs the editor of the collection-wide settings, saving after every change.
+    pub fn prompt_settings(&mut self) -> InquireResult<()> {
+        loop {
+            match self.day.prompt_edit()? {
+                EditStep::Changed => self.save()?,
+                EditStep::Back => return Ok(()),
+                _ => {}
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_bare_utc_as_zero() {
+        assert_eq!(parse_utc_offset("UTC"), Some(Some(0)));
+        assert_eq!(parse_utc_offset("utc"), Some(Some(0)));
+    }
+
+    #[test]
+    fn rejects_non_ascii_offsets() {
+        assert_eq!(parse_utc_offset("+1é1"), None);
+        assert_eq!(parse_utc_offset("UTC+é"), None);
+    }
+
+    #[test]
+    fn rejects_out_of_range_offsets() {
+        assert_eq!(parse_utc_offset("+99999999"), None);
+        assert_eq!(parse_utc_offset("-4294967295"), None);
+        assert_eq!(parse_utc_offset("+15"), None);
+        assert_eq!(parse_utc_offset("+14:00"), Some(Some(840)));
+    }
+}
@@ -0,0 +1,121 @@
+use std::fmt;
+
+use chrono::Duration;
+use inquire::{Confirm, Select};
+
+use crate::prelude::*;
+
+pub const FORECAST_RANGES: [i64; 3] = [30, 90, 365];
+const BAR_WIDTH: usize = 40;
+
+/// The number of reviews expected to come due on each of the coming days.
+#[derive(Clone, Debug)]
+pub struct Forecast {
+    pub start: NaiveDate,
+    /// The number of reviews due on each day from `start`, with overdue cards counted on
+    /// the first day.
+    pub counts: Vec<usize>,
+}
+
+impl Forecast {
+    /// Counts when each card beneath `node` is next due. If `simulate` is set, every card is
+    /// also assumed to be reviewed with `Grade::Good` whenever it comes due, and rescheduled
+    /// with its deck's options preset from `collection`, so cards coming back within the
+    /// forecast are counted again.
+    pub fn new(
+        collection: &Collection,
+        node: &DeckNode,
+        today: NaiveDate,
+        days: i64,
+        simulate: bool,
+    ) -> Self {
+        let end = today + Duration::days(days - 1);
+        let mut counts = vec![0; days as usize];
+
+        for deck in node.decks() {
+            let DeckNode::Deck { cards, .. } = deck else {
+                continue;
+            };
+            let preset = collection.preset_for(deck.id());
+
+            for card in cards.iter().filter(|card| !card.suspended) {
+                let mut card = card.clone();
+                while card.due <= end {
+                    let day = card.due.max(today);
+                    counts[(day - today).num_days() as usize] += 1;
+                    if !simulate {
+                        break;
+                    }
+
+                    preset.schedule(&mut card, Grade::Good, day);
+                    card.last_review = Some(day);
+                }
+            }
+        }
+
+        Self {
+            start: today,
+            counts,
+        }
+    }
+
+    /// The number of days summed up into each bar, so a forecast fits on one screen.
+    fn bucket_days(&self) -> usize {
+        match self.counts.len() {
+            0..=31 => 1,
+            32..=92 => 7,
+            _ => 30,
+        }
+    }
+}
+
+impl fmt::Display for Forecast {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let bucket_days = self.bucket_days();
+        let buckets: Vec<usize> = self
+            .counts
+            .chunks(bucket_days)
+            .map(|chunk| chunk.iter().sum())
+            .collect();
+        let max = buckets.iter().copied().max().unwrap_or(0).max(1);
+
+        for (i, count) in buckets.iter().enumerate() {
+            let day = self.start + Duration::days((i * bucket_days) as i64);
+            let bar = "█".repeat((count * BAR_WIDTH).div_ceil(max));
+            writeln!(
+                f,
+                "  {}  {:<width$} {}",
+                day.format("%b %d"),
+                bar,
+                count,
+                width = BAR_WIDTH
+            )?;
+        }
+        write!(
+            f,
+            "  Total: {} reviews over {} days",
+            self.counts.iter().sum::<usize>(),
+            self.counts.len()
+        )
+    }
+}
+
+impl Collection {
+    /// Asks which node and how far ahead to forecast, then shows the forecast.
+    pub fn prompt_forecast(&self) -> InquireResult<()> {
+        let path = self.root.prompt_node(ENTER_FORECAST_NODE)?;
+        let Some(node) = self.root.at(path) else {
+            return Ok(());
+        };
+
+        let days = Select::new(ENTER_FORECAST_DAYS, FORECAST_RANGES.to_vec()).prompt()?;
+        let simulate = Confirm::new(ASK_SIMULATE)
+            .with_default(true)
+            .with_help_message(HELP_SIMULATE)
+            .prompt()?;
+
+        let forecast = Forecast::new(self, node, self.today(), days, simulate);
+        println!("{}\n\n{}", node.display_name(), forecast);
+        Ok(())
+    }
+}
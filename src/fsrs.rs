@@ -0,0 +1,164 @@
+use chrono::Duration;
+use serde::{Deserialize, Serialize};
+
+use crate::prelude::*;
+
+/// The default FSRS-4.5 weights, fitted by the FSRS authors on a large body of review data.
+pub const FSRS_DEFAULT_WEIGHTS: [f64; 17] = [
+    0.4872, 1.4003, 3.7145, 13.8206, 5.1618, 1.2298, 0.8975, 0.031, 1.6474, 0.1367, 1.0461, 2.1072,
+    0.0793, 0.3246, 1.587, 0.2272, 2.8755,
+];
+pub const FSRS_DEFAULT_RETENTION: f64 = 0.9;
+pub const FSRS_MAXIMUM_INTERVAL: u32 = 36500;
+
+const DECAY: f64 = -0.5;
+const FACTOR: f64 = 19.0 / 81.0;
+
+/// What FSRS knows about how well a card is remembered.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct MemoryState {
+    /// The number of days after which the chance of recalling the card drops to 90%.
+    pub stability: f64,
+    /// How hard the card is to remember, from 1 to 10.
+    pub difficulty: f64,
+}
+
+/// The Free Spaced Repetition Scheduler, which models each card's memory as a stability and
+/// difficulty and schedules it for when its predicted recall drops to the desired retention.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Fsrs {
+    pub weights: [f64; 17],
+}
+
+impl Default for Fsrs {
+    fn default() -> Self {
+        Self {
+            weights: FSRS_DEFAULT_WEIGHTS,
+        }
+    }
+}
+
+impl Fsrs {
+    /// The probability of recalling a card `elapsed_days` after its last review.
+    pub fn retrievability(elapsed_days: f64, stability: f64) -> f64 {
+        (1.0 + FACTOR * elapsed_days / stability).powf(DECAY)
+    }
+
+    /// The number of days until the recall probability of a card falls to `retention`.
+    pub fn interval(stability: f64, retention: f64) -> f64 {
+        stability / FACTOR * (retention.powf(1.0 / DECAY) - 1.0)
+    }
+
+    fn rating(grade: Grade) -> f64 {
+        match grade {
+            Grade::Again => 1.0,
+            Grade::Hard => 2.0,
+            Grade::Good => 3.0,
+            Grade::Easy => 4.0,
+        }
+    }
+
+    fn initial_stability(&self, grade: Grade) -> f64 {
+        self.weights[Self::rating(grade) as usize - 1].max(0.1)
+    }
+
+    fn initial_difficulty(&self, rating: f64) -> f64 {
+        (self.weights[4] - (rating - 3.0) * self.weights[5]).clamp(1.0, 10.0)
+    }
+
+    fn next_difficulty(&self, difficulty: f64, grade: Grade) -> f64 {
+        let w = &self.weights;
+        let next = difficulty - w[6] * (Self::rating(grade) - 3.0);
+        let reverted = w[7] * self.initial_difficulty(3.0) + (1.0 - w[7]) * next;
+        reverted.clamp(1.0, 10.0)
+    }
+
+    fn recall_stability(&self, memory: MemoryState, r: f64, grade: Grade) -> f64 {
+        let w = &self.weights;
+        let hard_penalty = if grade == Grade::Hard { w[15] } else { 1.0 };
+        let easy_bonus = if grade == Grade::Easy { w[16] } else { 1.0 };
+        memory.stability
+            * (w[8].exp()
+                * (11.0 - memory.difficulty)
+                * memory.stability.powf(-w[9])
+                * ((w[10] * (1.0 - r)).exp() - 1.0)
+                * hard_penalty
+                * easy_bonus
+                + 1.0)
+    }
+
+    fn forget_stability(&self, memory: MemoryState, r: f64) -> f64 {
+        let w = &self.weights;
+        w[11]
+            * memory.difficulty.powf(-w[12])
+            * ((memory.stability + 1.0).powf(w[13]) - 1.0)
+            * (w[14] * (1.0 - r)).exp()
+    }
+
+    /// The memory state of a card after reviewing it with `grade`, `elapsed_days` after its
+    /// previous review, or for the first time if it has no memory state yet.
+    pub fn next_memory(
+        &self,
+        memory: Option<MemoryState>,
+        elapsed_days: f64,
+        grade: Grade,
+    ) -> MemoryState {
+        let Some(memory) = memory else {
+            return MemoryState {
+                stability: self.initial_stability(grade),
+                difficulty: self.initial_difficulty(Self::rating(grade)),
+            };
+        };
+
+        let r = Self::retrievability(elapsed_days, memory.stability);
+        let stability = match grade {
+            Grade::Again => self.forget_stability(memory, r),
+            _ => self.recall_stability(memory, r, grade),
+        };
+        MemoryState {
+            stability: stability.max(0.1),
+            difficulty: self.next_difficulty(memory.difficulty, grade),
+        }
+    }
+
+    /// A best guess at the memory state of a card that was so far scheduled by something else.
+    fn memory_from_interval(&self, card: &Card) -> Option<MemoryState> {
+        card.last_review?;
+        Some(MemoryState {
+            stability: (card.interval as f64).max(0.1),
+            difficulty: self.initial_difficulty(3.0),
+        })
+    }
+
+    /// Updates the card's memory state and due date after reviewing it on `today`, scheduling
+    /// it for when its predicted recall drops to `desired_retention`.
+    pub fn schedule(
+        &self,
+        card: &mut Card,
+        grade: Grade,
+        today: NaiveDate,
+        desired_retention: f64,
+    ) {
+        let memory = card.memory.or_else(|| self.memory_from_interval(card));
+        let elapsed_days = card
+            .last_review
+            .map_or(0, |last| (today - last).num_days().max(0)) as f64;
+        let memory = self.next_memory(memory, elapsed_days, grade);
+        card.memory = Some(memory);
+
+        if grade == Grade::Again {
+            if card.reps > 0 {
+                card.lapses += 1;
+            }
+            card.reps = 0;
+            card.interval = 0;
+            card.due = today;
+            return;
+        }
+
+        let interval = Self::interval(memory.stability, desired_retention).round() as u32;
+        card.reps += 1;
+        card.interval = interval.clamp(1, FSRS_MAXIMUM_INTERVAL);
+        card.due = today + Duration::days(card.interval as i64);
+    }
+}
@@ -0,0 +1,149 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fmt::Write,
+};
+
+use chrono::Duration;
+
+use crate::prelude::*;
+
+pub const HEATMAP_WEEKS: i64 = 53;
+
+/// Shades for each intensity level, from no reviews at all to the busiest days.
+const COLOR_SHADES: [&str; 5] = [
+    "\x1b[38;5;237m█",
+    "\x1b[38;5;22m█",
+    "\x1b[38;5;28m█",
+    "\x1b[38;5;34m█",
+    "\x1b[38;5;40m█",
+];
+const ASCII_SHADES: [char; 5] = ['.', '-', '+', '*', '#'];
+const RESET: &str = "\x1b[0m";
+
+/// The number of reviews on each day, drawn as a calendar of weeks like GitHub's
+/// contribution graph.
+#[derive(Clone, Debug)]
+pub struct Heatmap {
+    pub counts: BTreeMap<NaiveDate, usize>,
+    pub today: NaiveDate,
+}
+
+impl Heatmap {
+    /// Counts the reviews in `log` on each day, as `day` counts days.
+    pub fn new<'a>(
+        log: impl IntoIterator<Item = &'a ReviewLogEntry>,
+        day: &DaySettings,
+        today: NaiveDate,
+    ) -> Self {
+        let mut counts = BTreeMap::new();
+        for entry in log {
+            *counts.entry(day.day_of(entry.time)).or_insert(0) += 1;
+        }
+        Self { counts, today }
+    }
+
+    /// The number of days in a row with reviews, up to today. A day without reviews yet
+    /// doesn't break the streak until it is over.
+    pub fn current_streak(&self) -> usize {
+        let mut day = self.today;
+        if !self.counts.contains_key(&day) {
+            day -= Duration::days(1);
+        }
+
+        let mut streak = 0;
+        while self.counts.contains_key(&day) {
+            streak += 1;
+            day -= Duration::days(1);
+        }
+        streak
+    }
+
+    pub fn longest_streak(&self) -> usize {
+        let days: BTreeSet<NaiveDate> = self.counts.keys().copied().collect();
+        let mut longest = 0;
+        let mut streak = 0;
+        let mut previous: Option<NaiveDate> = None;
+
+        for day in days {
+            streak = match previous {
+                Some(previous) if day - previous == Duration::days(1) => streak + 1,
+                _ => 1,
+            };
+            longest = longest.max(streak);
+            previous = Some(day);
+        }
+        longest
+    }
+
+    /// The shade to draw a day with, from 0 for no reviews up to 4 for the busiest days.
+    fn level(&self, count: usize, max: usize) -> usize {
+        match count {
+            0 => 0,
+            _ => (count * 4).div_ceil(max.max(1)).clamp(1, 4),
+        }
+    }
+
+    /// Draws the last year of reviews, one column per week, with ANSI colors if `color` is
+    /// set and plain ASCII shades otherwise.
+    pub fn render(&self, color: bool) -> String {
+        let weekday = self.today.weekday().num_days_from_monday() as i64;
+        let start = self.today - Duration::days(weekday + (HEATMAP_WEEKS - 1) * 7);
+        let max = self
+            .counts
+            .range(start..=self.today)
+            .map(|(_, count)| *count)
+            .max()
+            .unwrap_or(0);
+
+        let mut out = String::new();
+
+        // Month names above the first week in which each month starts
+        let mut months = String::from("    ");
+        let mut week = 0;
+        while week < HEATMAP_WEEKS {
+            let monday = start + Duration::days(week * 7);
+            let first_of_month = (0..7)
+                .map(|day| monday + Duration::days(day))
+                .find(|day| day.day() == 1);
+            if let Some(day) = first_of_month {
+                let name = day.format("%b").to_string();
+                months.push_str(&name);
+                week += name.len() as i64;
+            } else {
+                months.push(' ');
+                week += 1;
+            }
+        }
+        let _ = writeln!(out, "{}", months.trim_end());
+
+        for (row, label) in ["Mon", "", "Wed", "", "Fri", "", "Sun"].iter().enumerate() {
+            let _ = write!(out, "{:<4}", label);
+            for week in 0..HEATMAP_WEEKS {
+                let day = start + Duration::days(week * 7 + row as i64);
+                if day > self.today {
+                    break;
+                }
+
+                let count = self.counts.get(&day).copied().unwrap_or(0);
+                let level = self.level(count, max);
+                if color {
+                    out.push_str(COLOR_SHADES[level]);
+                } else {
+                    out.push(ASCII_SHADES[level]);
+                }
+            }
+            if color {
+                out.push_str(RESET);
+            }
+            out.push('\n');
+        }
+
+        let _ = write!(
+            out,
+            "Current streak: {} days   Longest streak: {} days",
+            self.current_streak(),
+            self.longest_streak()
+        );
+        out
+    }
+}
@@ -0,0 +1,89 @@
+use chrono::Duration;
+use serde::{Deserialize, Serialize};
+
+use crate::prelude::*;
+
+/// How far ahead, in minutes, a card still in its learning steps may be shown when there is
+/// nothing else left to study.
+pub const LEARN_AHEAD_MINUTES: i64 = 20;
+
+/// Where a card is in the short delays it goes through within a day, before being scheduled
+/// a whole number of days ahead.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LearningStep {
+    /// The index of the step in the preset's learning or relearning steps.
+    pub index: usize,
+    /// Whether the card is being relearned after being forgotten, rather than learned anew.
+    pub relearning: bool,
+}
+
+impl Preset {
+    /// The delays, in minutes, of the learning or relearning steps.
+    fn steps(&self, relearning: bool) -> &[u32] {
+        if relearning {
+            &self.relearning_steps
+        } else {
+            &self.learning_steps
+        }
+    }
+
+    /// Reschedules `card` after a review at `now`, with days counted by `day`.
+    ///
+    /// New cards go through the learning steps and forgotten ones through the relearning
+    /// steps, being shown again once each step's delay has passed. Again goes back to the
+    /// first step, Hard repeats the current one, Good moves on to the next and Easy skips
+    /// the rest. Past the last step the card graduates, and from then on it is scheduled by
+    /// the preset's algorithm.
+    pub fn review(&self, card: &mut Card, grade: Grade, now: DateTime<Utc>, day: &DaySettings) {
+        let today = day.day_of(now);
+        let step = match card.learning {
+            Some(step) => step,
+            None if card.last_review.is_none() && !self.learning_steps.is_empty() => LearningStep {
+                index: 0,
+                relearning: false,
+            },
+            None => {
+                self.schedule(card, grade, today);
+                if grade == Grade::Again && !self.relearning_steps.is_empty() {
+                    let step = LearningStep {
+                        index: 0,
+                        relearning: true,
+                    };
+                    self.enter_step(card, step, now, day);
+                }
+                return;
+            }
+        };
+
+        let steps = self.steps(step.relearning);
+        let index = match grade {
+            Grade::Again => 0,
+            Grade::Hard => step.index,
+            Grade::Good => step.index + 1,
+            Grade::Easy => steps.len(),
+        };
+        if index < steps.len() {
+            self.enter_step(card, LearningStep { index, ..step }, now, day);
+        } else {
+            card.learning = None;
+            card.due_at = None;
+            self.schedule(card, grade, today);
+        }
+    }
+
+    /// Puts `card` at `step`, due again once its delay has passed after `now`.
+    fn enter_step(
+        &self,
+        card: &mut Card,
+        step: LearningStep,
+        now: DateTime<Utc>,
+        day: &DaySettings,
+    ) {
+        let minutes = self.steps(step.relearning)[step.index];
+        let due_at = now + Duration::minutes(minutes as i64);
+        card.learning = Some(step);
+        card.due_at = Some(due_at);
+        card.due = day.day_of(due_at);
+        card.interval = 0;
+    }
+}
@@ -0,0 +1,158 @@
+use std::fmt;
+
+use inquire::{validator::Validation, CustomType, Select};
+use serde::{Deserialize, Serialize};
+use ulid::Ulid;
+
+use crate::prelude::*;
+
+/// The tag given to cards found to be leeches.
+pub const LEECH_TAG: &str = "leech";
+pub const LEECH_DEFAULT_THRESHOLD: u32 = 8;
+
+/// What happens to a card once it turns out to be a leech.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum LeechAction {
+    /// Only tag it, leaving it in the study queue.
+    #[default]
+    Tag,
+    /// Tag it and suspend it, so it stops coming up until it has been rewritten.
+    Suspend,
+}
+
+impl fmt::Display for LeechAction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                Self::Tag => LEECH_TAG_ONLY,
+                Self::Suspend => LEECH_SUSPEND,
+            }
+        )
+    }
+}
+
+/// How a deck spots cards that keep being forgotten, and what it does with them.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LeechOptions {
+    /// The number of lapses from which a card counts as a leech.
+    pub threshold: u32,
+    pub action: LeechAction,
+}
+
+impl Default for LeechOptions {
+    fn default() -> Self {
+        Self {
+            threshold: LEECH_DEFAULT_THRESHOLD,
+            action: LeechAction::default(),
+        }
+    }
+}
+
+impl LeechOptions {
+    /// Whether a card that has just lapsed for the `lapses`th time should be flagged: once
+    /// it reaches the threshold, and again every half threshold after that, as long as it
+    /// keeps being forgotten.
+    pub fn is_leech(&self, lapses: u32) -> bool {
+        let threshold = self.threshold.max(1);
+        lapses >= threshold && (lapses - threshold).is_multiple_of(threshold.div_ceil(2))
+    }
+
+    /// Flags `card` as a leech if its latest lapse made it one, returning whether it did.
+    pub fn check(&self, card: &mut Card) -> bool {
+        if !self.is_leech(card.lapses) {
+            return false;
+        }
+
+        card.tags.insert(LEECH_TAG.to_owned());
+        if self.action == LeechAction::Suspend {
+            card.suspended = true;
+        }
+        true
+    }
+
+    pub fn prompt_new(&self) -> InquireResult<Self> {
+        let threshold = CustomType::new(ENTER_LEECH_THRESHOLD)
+            .with_default(self.threshold)
+            .with_help_message(HELP_LEECH_THRESHOLD)
+            .with_validator(|threshold: &u32| {
+                Ok(if *threshold > 0 {
+                    Validation::Valid
+                } else {
+                    Validation::Invalid(INVALID_LEECH_THRESHOLD.into())
+                })
+            })
+            .prompt()?;
+
+        let actions = vec![LeechAction::Tag, LeechAction::Suspend];
+        let cursor = actions.iter().position(|a| *a == self.action).unwrap_or(0);
+        let action = Select::new(ENTER_LEECH_ACTION, actions)
+            .with_starting_cursor(cursor)
+            .prompt()?;
+
+        Ok(Self { threshold, action })
+    }
+}
+
+impl fmt::Display for LeechOptions {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} lapses, {}", self.threshold, self.action)
+    }
+}
+
+impl DeckNode {
+    /// Every card beneath this node that has been flagged as a leech.
+    pub fn leeches(&self) -> Vec<&Card> {
+        self.cards()
+            .into_iter()
+            .filter(|card| card.has_tag(LEECH_TAG))
+            .collect()
+    }
+}
+
+impl Collection {
+    /// Asks which node to look for leeches under, then lists them so they can be rewritten
+    /// through the card editor.
+    pub fn prompt_leeches(&mut self) -> InquireResult<()> {
+        struct Entry {
+            id: Option<Ulid>,
+            name: String,
+        }
+
+        impl fmt::Display for Entry {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                write!(f, "{}", self.name)
+            }
+        }
+
+        let path = self.root.prompt_node(ENTER_LEECHES_NODE)?;
+        loop {
+            let Some(node) = self.root.at(path.clone()) else {
+                return Ok(());
+            };
+
+            let mut options: Vec<Entry> = node
+                .leeches()
+                .into_iter()
+                .map(|card| Entry {
+                    id: Some(card.id),
+                    name: format!("{} ({} lapses)", card.display_name(), card.lapses),
+                })
+                .collect();
+            if options.is_empty() {
+                println!("{}", NO_LEECHES);
+                return Ok(());
+            }
+            options.push(Entry {
+                id: None,
+                name: BACK.to_owned(),
+            });
+
+            match Select::new(LEECHES, options).prompt()?.id {
+                Some(id) => self.prompt_edit_card(id)?,
+                None => return Ok(()),
+            }
+        }
+    }
+}
@@ -0,0 +1,108 @@
+use chrono::Duration;
+use inquire::{validator::Validation, Text};
+use serde::{Deserialize, Serialize};
+
+use crate::prelude::*;
+
+pub const LEITNER_DEFAULT_INTERVALS: [u32; 5] = [1, 3, 7, 14, 30];
+
+/// The Leitner system: every card sits in a numbered box, each reviewed at its own interval.
+/// Remembered cards move up a box, and forgotten ones go all the way back to the first.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Leitner {
+    /// The number of days between reviews for the cards in each box, first box first.
+    pub intervals: Vec<u32>,
+}
+
+impl Default for Leitner {
+    fn default() -> Self {
+        Self {
+            intervals: LEITNER_DEFAULT_INTERVALS.to_vec(),
+        }
+    }
+}
+
+impl Leitner {
+    /// The intervals of the boxes, falling back to the default ones if none were saved, so
+    /// there is always at least one box.
+    pub fn intervals(&self) -> &[u32] {
+        if self.intervals.is_empty() {
+            &LEITNER_DEFAULT_INTERVALS
+        } else {
+            &self.intervals
+        }
+    }
+
+    /// The index of the box the card is in, accounting for boxes that have since been removed.
+    pub fn box_of(&self, card: &Card) -> usize {
+        card.leitner_box.min(self.intervals().len() - 1)
+    }
+
+    /// The number of cards in each box.
+    pub fn box_counts<'a>(&self, cards: impl IntoIterator<Item = &'a Card>) -> Vec<usize> {
+        let mut counts = vec![0; self.intervals().len()];
+        for card in cards {
+            counts[self.box_of(card)] += 1;
+        }
+        counts
+    }
+
+    pub fn display_boxes<'a>(&self, cards: impl IntoIterator<Item = &'a Card>) -> String {
+        let boxes: Vec<String> = self
+            .box_counts(cards)
+            .iter()
+            .enumerate()
+            .map(|(i, count)| format!("{}:{}", i + 1, count))
+            .collect();
+        format!("📦 {}", boxes.join(" "))
+    }
+
+    pub fn prompt_intervals(&self) -> InquireResult<Vec<u32>> {
+        let current: Vec<String> = self.intervals().iter().map(|i| i.to_string()).collect();
+        let input = Text::new(ENTER_LEITNER_INTERVALS)
+            .with_default(&current.join(", "))
+            .with_help_message(HELP_LEITNER_INTERVALS)
+            .with_validator(|input: &str| {
+                Ok(match parse_intervals(input) {
+                    Some(_) => Validation::Valid,
+                    None => Validation::Invalid(INVALID_LEITNER_INTERVALS.into()),
+                })
+            })
+            .prompt()?;
+        Ok(parse_intervals(&input).expect("validated above"))
+    }
+}
+
+impl Scheduler for Leitner {
+    fn schedule(&self, card: &mut Card, grade: Grade, today: NaiveDate) {
+        let current = self.box_of(card);
+        card.leitner_box = match grade {
+            Grade::Again => 0,
+            Grade::Hard => current,
+            Grade::Good | Grade::Easy => (current + 1).min(self.intervals().len() - 1),
+        };
+
+        if grade == Grade::Again {
+            if card.reps > 0 {
+                card.lapses += 1;
+            }
+            card.reps = 0;
+            card.interval = 0;
+            card.due = today;
+            return;
+        }
+
+        card.reps += 1;
+        card.interval = self.intervals()[card.leitner_box];
+        card.due = today + Duration::days(card.interval as i64);
+    }
+}
+
+/// Parses a comma-separated list of at least one positive number of days.
+fn parse_intervals(input: &str) -> Option<Vec<u32>> {
+    let intervals: Vec<u32> = input
+        .split(',')
+        .map(|part| part.trim().parse().ok().filter(|days| *days > 0))
+        .collect::<Option<_>>()?;
+    (!intervals.is_empty()).then_some(intervals)
+}
@@ -1,12 +1,52 @@
+pub mod browser;
 pub mod card;
+pub mod cloze;
+pub mod collection;
+pub mod day;
+pub mod forecast;
+pub mod fsrs;
+pub mod heatmap;
+pub mod learning;
+pub mod leech;
+pub mod leitner;
+pub mod limits;
 pub mod node;
+pub mod note_type;
+pub mod optimizer;
+pub mod preset;
+pub mod review_log;
+pub mod scheduler;
+pub mod stats;
+pub mod study;
+pub mod tags;
+pub mod typing;
 pub mod util;
 
 pub mod prelude {
     pub use chrono::prelude::*;
     pub use inquire::error::InquireResult;
 
+    pub use crate::browser::*;
     pub use crate::card::*;
+    pub use crate::cloze::*;
+    pub use crate::collection::*;
+    pub use crate::day::*;
+    pub use crate::forecast::*;
+    pub use crate::fsrs::*;
+    pub use crate::heatmap::*;
+    pub use crate::learning::*;
+    pub use crate::leech::*;
+    pub use crate::leitner::*;
+    pub use crate::limits::*;
     pub use crate::node::*;
+    pub use crate::note_type::*;
+    pub use crate::optimizer::*;
+    pub use crate::preset::*;
+    pub use crate::review_log::*;
+    pub use crate::scheduler::*;
+    pub use crate::stats::*;
+    pub use crate::study::*;
+    pub use crate::tags::*;
+    pub use crate::typing::*;
     pub use crate::util::*;
 }
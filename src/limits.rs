@@ -0,0 +1,155 @@
+use std::{collections::HashMap, fmt};
+
+use ulid::Ulid;
+
+use crate::prelude::*;
+
+/// The cards to study today beneath a node, within the daily limits of the node and of every
+/// node beneath it. Cards in learning steps aren't limited, as they were already counted on
+/// the day they were introduced or forgotten.
+#[derive(Clone, Debug, Default)]
+pub struct StudyQueue {
+    /// Cards never studied before.
+    pub new: Vec<Ulid>,
+    /// Cards studied before that have come due again.
+    pub review: Vec<Ulid>,
+    /// Cards in a learning or relearning step that are due at some point today.
+    pub learning: Vec<Ulid>,
+}
+
+/// How many new, learning and review cards there are, or are left, for a day.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct StudyCounts {
+    pub new: usize,
+    pub review: usize,
+    pub learning: usize,
+}
+
+impl fmt::Display for StudyCounts {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "🆕 {} 📖 {} 🔁 {}", self.new, self.learning, self.review)
+    }
+}
+
+impl StudyQueue {
+    pub fn counts(&self) -> StudyCounts {
+        StudyCounts {
+            new: self.new.len(),
+            review: self.review.len(),
+            learning: self.learning.len(),
+        }
+    }
+
+    /// Cuts the queue down to what `preset` still allows for the day, given what has already
+    /// been `studied`.
+    fn limit(&mut self, preset: &Preset, studied: StudyCounts) {
+        let new = (preset.new_per_day as usize).saturating_sub(studied.new);
+        let review = (preset.reviews_per_day as usize).saturating_sub(studied.review);
+        self.new.truncate(new);
+        self.review.truncate(review);
+    }
+}
+
+/// The number of cards among `cards` first studied today, and of the others reviewed today,
+/// given whether each card reviewed today was `studied` for the first time.
+fn studied_among<'a>(
+    cards: impl IntoIterator<Item = &'a Card>,
+    studied: &HashMap<Ulid, bool>,
+) -> StudyCounts {
+    let mut counts = StudyCounts::default();
+    for card in cards {
+        match studied.get(&card.id) {
+            Some(true) => counts.new += 1,
+            Some(false) => counts.review += 1,
+            None => {}
+        }
+    }
+    counts
+}
+
+impl Collection {
+    /// The cards to study today beneath the node with the given id, only counting those
+    /// tagged `tag` if given. Each deck contributes no more than its preset allows, and each
+    /// set caps the total of everything beneath it.
+    pub fn study_queue(&self, id: Ulid, tag: Option<&str>, today: NaiveDate) -> StudyQueue {
+        let Some(node) = self.root.find(id) else {
+            return StudyQueue::default();
+        };
+        let inherited = self.root.preset_of(id);
+        let studied = self.studied_on(today);
+        self.limited_queue(node, inherited, tag, today, &studied, &mut HashMap::new())
+    }
+
+    /// How many cards of each kind are left to study today at every node in the tree.
+    pub fn study_counts(&self, today: NaiveDate) -> HashMap<Ulid, StudyCounts> {
+        let mut counts = HashMap::new();
+        let studied = self.studied_on(today);
+        self.limited_queue(&self.root, None, None, today, &studied, &mut counts);
+        counts
+    }
+
+    /// Every card reviewed on `today` according to the review log, along with whether it
+    /// was studied for the first time then.
+    fn studied_on(&self, today: NaiveDate) -> HashMap<Ulid, bool> {
+        let mut first_days: HashMap<Ulid, NaiveDate> = HashMap::new();
+        let mut studied = HashMap::new();
+        for entry in self.history() {
+            let day = self.day.day_of(entry.time);
+            let first_day = *first_days.entry(entry.card).or_insert(day);
+            if day == today {
+                studied.insert(entry.card, first_day == today);
+            }
+        }
+        studied
+    }
+
+    /// Builds the study queue of `node`, whose parent uses the preset `inherited`, out of the
+    /// cards tagged `tag` if given, recording the counts of it and every node beneath it in
+    /// `counts`. `studied` holds the cards reviewed today, as returned by `studied_on`.
+    fn limited_queue(
+        &self,
+        node: &DeckNode,
+        inherited: Option<Ulid>,
+        tag: Option<&str>,
+        today: NaiveDate,
+        studied: &HashMap<Ulid, bool>,
+        counts: &mut HashMap<Ulid, StudyCounts>,
+    ) -> StudyQueue {
+        let preset = node.preset().or(inherited);
+        let mut queue = match node {
+            DeckNode::Set { entries, .. } => {
+                let mut queue = StudyQueue::default();
+                for entry in entries {
+                    let child = self.limited_queue(entry, preset, tag, today, studied, counts);
+                    queue.new.extend(child.new);
+                    queue.review.extend(child.review);
+                    queue.learning.extend(child.learning);
+                }
+                queue
+            }
+            DeckNode::Deck { cards, .. } => {
+                let mut queue = StudyQueue::default();
+                for card in cards {
+                    if !card.is_due(today)
+                        || !card.is_active(today)
+                        || tag.is_some_and(|tag| !card.has_tag(tag))
+                    {
+                        continue;
+                    }
+                    if card.learning.is_some() {
+                        queue.learning.push(card.id);
+                    } else if card.last_review.is_none() {
+                        queue.new.push(card.id);
+                    } else {
+                        queue.review.push(card.id);
+                    }
+                }
+                queue
+            }
+        };
+
+        queue.limit(self.preset(preset), studied_among(node.cards(), studied));
+        counts.insert(node.id(), queue.counts());
+        queue
+    }
+}
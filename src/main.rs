@@ -1,39 +1,70 @@
-use inquire::Select;
+use inquire::{InquireError, Select};
 use srs::prelude::*;
 use std::fmt;
 
 /// The main entry point of the program, run when the user opens the app.
 fn main() {
-    // Set up the root DeckNode, holding the hierarchy of user-created decks
-    let mut root = DeckNode::set(
+    // Find where the user's collection lives, and load it from disk. On the very first run
+    // there is nothing to load yet, so start out with a small sample hierarchy instead.
+    let collection = collection_path().and_then(|path| {
+        Ok(match Collection::load(&path)? {
+            Some(collection) => collection,
+            None => Collection::new(sample_root(DaySettings::default().today()), path),
+        })
+    });
+
+    // If the collection exists but can't be read, bail out rather than risk overwriting it
+    let mut collection = match collection {
+        Ok(collection) => collection,
+        Err(err) => {
+            eprintln!("failed to load collection: {}", err);
+            std::process::exit(1);
+        }
+    };
+
+    // Prompt the user with the options in the main menu. Leaving it with Escape or Ctrl-C
+    // is how the user quits, but any other error means a change may not have been saved.
+    match prompt_main(&mut collection) {
+        Ok(()) | Err(InquireError::OperationCanceled | InquireError::OperationInterrupted) => {}
+        Err(err) => {
+            eprintln!("error: {}", err);
+            std::process::exit(1);
+        }
+    }
+}
+
+/// The root DeckNode a new collection starts with, holding a hierarchy of example decks
+/// whose cards are due `today`.
+fn sample_root(today: NaiveDate) -> DeckNode {
+    DeckNode::set(
         "All Decks",
         [
             DeckNode::set(
                 "French",
                 [DeckNode::deck("Vocab", [
-                    Card::new("manger", "to eat"),
-                    Card::new("voir", "to see"),
-                    Card::new("faire", "to do"),
-                    Card::new("gravir", "to climb"),
+                    Card::new("manger", "to eat", today),
+                    Card::new("voir", "to see", today),
+                    Card::new("faire", "to do", today),
+                    Card::new("gravir", "to climb", today),
                 ]), DeckNode::deck("Nouns", [])],
             ),
             DeckNode::deck("Comp Sci", []),
         ],
-    );
-
-    // Prompt the user with the options in the main menu
-    // Since this returns an `InquireResult<()>`, and a Result must be handled (it could
-    // potentially be an error!), we must assign it to *something*.
-    // We don't care about if we have an error, so just assign it to nothing (`_`).
-    let _ = prompt_main(&mut root);
+    )
 }
 
 /// Shows the main menu to the user. This uses the [inquire](https://docs.rs/inquire/) library,
 /// allowing accepting validated user input easily.
-fn prompt_main(root: &mut DeckNode) -> InquireResult<()> {
+fn prompt_main(collection: &mut Collection) -> InquireResult<()> {
     enum Option {
         Deck { opt: NodePromptOption, index: usize },
         Stats,
+        Forecast,
+        NoteTypes,
+        Tags,
+        Browse,
+        Leeches,
+        Settings,
         Quit,
     }
 
@@ -42,6 +73,12 @@ fn prompt_main(root: &mut DeckNode) -> InquireResult<()> {
             match self {
                 Option::Deck { opt, .. } => opt.fmt(f),
                 Option::Stats => write!(f, "{}", STATS),
+                Option::Forecast => write!(f, "{}", FORECAST),
+                Option::NoteTypes => write!(f, "{}", NOTE_TYPES),
+                Option::Tags => write!(f, "{}", TAGS),
+                Option::Browse => write!(f, "{}", BROWSE),
+                Option::Leeches => write!(f, "{}", LEECHES),
+                Option::Settings => write!(f, "{}", SETTINGS),
                 Option::Quit => write!(f, "{}", QUIT),
             }
         }
@@ -49,13 +86,21 @@ fn prompt_main(root: &mut DeckNode) -> InquireResult<()> {
 
     let mut last_cursor = 0;
     loop {
-        let mut options: Vec<Option> = root
-            .prompt_options()
+        let counts = collection.study_counts(collection.today());
+        let mut options: Vec<Option> = collection
+            .root
+            .prompt_options(&counts)
             .into_iter()
             .enumerate()
             .map(|(index, opt)| Option::Deck { opt, index })
             .collect();
         options.push(Option::Stats);
+        options.push(Option::Forecast);
+        options.push(Option::NoteTypes);
+        options.push(Option::Tags);
+        options.push(Option::Browse);
+        options.push(Option::Leeches);
+        options.push(Option::Settings);
         options.push(Option::Quit);
 
         match Select::new(MAIN_MENU, options)
@@ -63,14 +108,16 @@ fn prompt_main(root: &mut DeckNode) -> InquireResult<()> {
             .prompt()?
         {
             Option::Deck { opt, index } => {
-                let Some(target) = root.at_mut(opt.path) else {
-                    panic!("invalid target");
-                };
-
-                target.prompt_select(opt.action)?;
+                collection.prompt_select(opt.path, opt.action)?;
                 last_cursor = index;
             }
-            Option::Stats => return Ok(()),
+            Option::Stats => collection.prompt_stats()?,
+            Option::Forecast => collection.prompt_forecast()?,
+            Option::NoteTypes => collection.prompt_note_types()?,
+            Option::Tags => collection.prompt_tags()?,
+            Option::Browse => collection.prompt_browse()?,
+            Option::Leeches => collection.prompt_leeches()?,
+            Option::Settings => collection.prompt_settings()?,
             Option::Quit => {
                 if prompt_confirm()? {
                     return Ok(());
@@ -1,19 +1,36 @@
-use std::{collections::VecDeque, fmt};
+use std::{
+    collections::{HashMap, VecDeque},
+    fmt,
+};
 
 use inquire::{MultiSelect, Select, Text};
+use serde::{Deserialize, Serialize};
+use ulid::Ulid;
 
 use crate::prelude::*;
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub enum DeckNode {
     Set {
+        #[serde(default = "Ulid::new")]
+        id: Ulid,
         name: String,
         entries: Vec<DeckNode>,
         expanded: bool,
+        /// The options preset assigned to the set, if it doesn't use its parent's.
+        #[serde(default)]
+        preset: Option<Ulid>,
     },
     Deck {
+        #[serde(default = "Ulid::new")]
+        id: Ulid,
         name: String,
         cards: Vec<Card>,
+        /// The options preset assigned to the deck, if it doesn't use its parent's.
+        #[serde(default)]
+        preset: Option<Ulid>,
+        #[serde(default)]
+        leech: LeechOptions,
     },
 }
 
@@ -23,19 +40,44 @@ pub enum NodeAction {
     ToggleExpanded,
 }
 
+/// The outcome of a single interaction with an edit menu.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum EditStep {
+    Changed,
+    Unchanged,
+    Study,
+    Optimize,
+    /// The user wants to change the node's options preset.
+    Options,
+    /// The user picked a card to edit.
+    EditCard(Ulid),
+    /// The user picked a node to move elsewhere in the tree.
+    MoveNode(Ulid),
+    /// The user picked cards to move to another deck.
+    MoveCards(Vec<Ulid>),
+    /// The user picked cards to remove, and confirmed it.
+    RemoveCards(Vec<Ulid>),
+    Back,
+}
+
 impl DeckNode {
     pub fn set(name: impl Into<String>, entries: impl Into<Vec<Self>>) -> Self {
         Self::Set {
+            id: Ulid::new(),
             name: name.into(),
             entries: entries.into(),
             expanded: true,
+            preset: None,
         }
     }
 
     pub fn deck(name: impl Into<String>, cards: impl Into<Vec<Card>>) -> Self {
         Self::Deck {
+            id: Ulid::new(),
             name: name.into(),
             cards: cards.into(),
+            preset: None,
+            leech: LeechOptions::default(),
         }
     }
 
@@ -49,29 +91,284 @@ impl DeckNode {
         Ok(Self::deck(name, []))
     }
 
+    pub fn id(&self) -> Ulid {
+        match self {
+            Self::Set { id, .. } | Self::Deck { id, .. } => *id,
+        }
+    }
+
+    fn entries(&self) -> &[Self] {
+        match self {
+            Self::Set { entries, .. } => entries,
+            Self::Deck { .. } => &[],
+        }
+    }
+
+    /// Resolves `path` by following the node ids in it. If a node has since been moved
+    /// elsewhere in the tree, it is still found by its id.
     pub fn at(&self, path: DeckPath) -> Option<&Self> {
-        let mut path = path.clone();
-        let Some(next_key) = path.0.pop_front() else {
+        match path.0.back() {
+            Some(id) => self.walk(path.0.iter()).or_else(|| self.find(*id)),
+            None => Some(self),
+        }
+    }
+
+    pub fn at_mut(&mut self, path: DeckPath) -> Option<&mut Self> {
+        let target = self.at(path)?.id();
+        self.find_mut(target)
+    }
+
+    fn walk<'a>(&self, mut ids: impl Iterator<Item = &'a Ulid>) -> Option<&Self> {
+        let Some(next_id) = ids.next() else {
             return Some(self);
         };
 
+        self.entries()
+            .iter()
+            .find(|entry| entry.id() == *next_id)
+            .and_then(|next| next.walk(ids))
+    }
+
+    /// Finds the node with the given id anywhere beneath (or at) this one.
+    pub fn find(&self, id: Ulid) -> Option<&Self> {
+        if self.id() == id {
+            return Some(self);
+        }
+        self.entries().iter().find_map(|entry| entry.find(id))
+    }
+
+    pub fn find_mut(&mut self, id: Ulid) -> Option<&mut Self> {
+        if self.id() == id {
+            return Some(self);
+        }
         match self {
-            Self::Set { entries, .. } => entries.get(next_key).and_then(|next| next.at(path)),
+            Self::Set { entries, .. } => entries.iter_mut().find_map(|entry| entry.find_mut(id)),
             Self::Deck { .. } => None,
         }
     }
 
-    pub fn at_mut(&mut self, path: DeckPath) -> Option<&mut Self> {
-        let mut path = path.clone();
-        let Some(next_key) = path.0.pop_front() else {
-            return Some(self);
+    /// The options preset assigned to this node itself.
+    pub fn preset(&self) -> Option<Ulid> {
+        match self {
+            Self::Set { preset, .. } | Self::Deck { preset, .. } => *preset,
+        }
+    }
+
+    pub fn set_preset(&mut self, id: Option<Ulid>) {
+        match self {
+            Self::Set { preset, .. } | Self::Deck { preset, .. } => *preset = id,
+        }
+    }
+
+    /// The options preset used by the node with the given id: its own, or else the one of
+    /// the closest set above it that has one.
+    pub fn preset_of(&self, id: Ulid) -> Option<Ulid> {
+        fn walk(this: &DeckNode, id: Ulid, inherited: Option<Ulid>) -> Option<Option<Ulid>> {
+            let inherited = this.preset().or(inherited);
+            if this.id() == id {
+                return Some(inherited);
+            }
+            this.entries()
+                .iter()
+                .find_map(|entry| walk(entry, id, inherited))
+        }
+
+        walk(self, id, None).flatten()
+    }
+
+    /// Finds the card with the given id anywhere beneath this node.
+    pub fn find_card(&self, id: Ulid) -> Option<&Card> {
+        match self {
+            Self::Set { entries, .. } => entries.iter().find_map(|entry| entry.find_card(id)),
+            Self::Deck { cards, .. } => cards.iter().find(|card| card.id == id),
+        }
+    }
+
+    pub fn find_card_mut(&mut self, id: Ulid) -> Option<&mut Card> {
+        self.deck_of_mut(id)?
+            .cards_mut()
+            .iter_mut()
+            .find(|card| card.id == id)
+    }
+
+    /// Finds the deck holding the card with the given id.
+    pub fn deck_of_mut(&mut self, card: Ulid) -> Option<&mut Self> {
+        match self {
+            Self::Set { entries, .. } => {
+                entries.iter_mut().find_map(|entry| entry.deck_of_mut(card))
+            }
+            Self::Deck { cards, .. } => {
+                if cards.iter().any(|c| c.id == card) {
+                    Some(self)
+                } else {
+                    None
+                }
+            }
+        }
+    }
+
+    /// Every card beneath this node, in the order they appear in the tree.
+    pub fn cards_mut_all(&mut self) -> Vec<&mut Card> {
+        match self {
+            Self::Set { entries, .. } => entries
+                .iter_mut()
+                .flat_map(|entry| entry.cards_mut_all())
+                .collect(),
+            Self::Deck { cards, .. } => cards.iter_mut().collect(),
+        }
+    }
+
+    /// Applies the contents of the card with the given id to every other card generated from
+    /// the same note. For cloze notes, cards are also added for new deletion numbers, due
+    /// `today`, and removed for ones no longer in the text.
+    pub fn sync_note(&mut self, id: Ulid, note_types: &[NoteType], today: NaiveDate) {
+        let Some(source) = self.find_card(id).cloned() else {
+            return;
+        };
+        let Some(source_deck) = self.deck_of_mut(id).map(|deck| deck.id()) else {
+            return;
+        };
+        for card in self.cards_mut_all() {
+            card.sync_from(&source);
+            if source.note.is_some() && card.note == source.note {
+                for note_type in note_types {
+                    note_type.render(card);
+                }
+            }
+        }
+
+        let (CardKind::Cloze(_), Some(note)) = (source.kind, source.note) else {
+            return;
         };
+        let numbers = cloze_numbers(&source.front);
+        self.retain_cards(&mut |card| match card.kind {
+            CardKind::Cloze(number) if card.note == Some(note) => numbers.contains(&number),
+            _ => true,
+        });
+
+        let existing: Vec<u32> = self
+            .cards()
+            .into_iter()
+            .filter(|card| card.note == Some(note))
+            .filter_map(|card| match card.kind {
+                CardKind::Cloze(number) => Some(number),
+                _ => None,
+            })
+            .collect();
+        let Some(Self::Deck { cards, .. }) = self.find_mut(source_deck) else {
+            return;
+        };
+        for number in numbers.into_iter().filter(|n| !existing.contains(n)) {
+            cards.push(Card {
+                kind: CardKind::Cloze(number),
+                note: Some(note),
+                ..Card::new(source.front.clone(), source.back.clone(), today)
+            });
+        }
+    }
+
+    /// Brings the cards generated from `note_type` up to date with it: renders them again, and
+    /// gives every note a card for any template it has none for yet, in the deck holding the
+    /// note's other cards. Those new cards are due `today`.
+    pub fn sync_note_type(&mut self, note_type: &NoteType, today: NaiveDate) {
+        struct Note {
+            deck: Ulid,
+            id: Option<Ulid>,
+            fields: Vec<String>,
+            templates: Vec<Ulid>,
+        }
+
+        let mut notes: Vec<Note> = Vec::new();
+        for deck in self.decks_mut() {
+            let deck_id = deck.id();
+            let Self::Deck { cards, .. } = deck else {
+                continue;
+            };
+            for card in cards.iter_mut() {
+                let CardKind::Template {
+                    note_type: id,
+                    template,
+                } = card.kind
+                else {
+                    continue;
+                };
+                if id != note_type.id {
+                    continue;
+                }
+                note_type.render(card);
+
+                match notes.iter_mut().find(|note| note.id == card.note) {
+                    Some(note) => note.templates.push(template),
+                    None => notes.push(Note {
+                        deck: deck_id,
+                        id: card.note,
+                        fields: card.fields.clone(),
+                        templates: vec![template],
+                    }),
+                }
+            }
+        }
+
+        for note in notes {
+            let Some(Self::Deck { cards, .. }) = self.find_mut(note.deck) else {
+                continue;
+            };
+            for template in &note_type.templates {
+                if !note.templates.contains(&template.id) {
+                    cards.push(note_type.card(template, note.id, note.fields.clone(), today));
+                }
+            }
+        }
+    }
 
+    /// Removes the cards with the given ids from beneath this node, in whichever deck they
+    /// are. Other cards generated from the same notes are kept.
+    pub fn remove_cards(&mut self, ids: &[Ulid]) {
+        self.retain_cards(&mut |card| !ids.contains(&card.id));
+    }
+
+    /// Removes every card beneath this node for which `keep` returns false.
+    pub fn retain_cards(&mut self, keep: &mut dyn FnMut(&Card) -> bool) {
         match self {
             Self::Set { entries, .. } => {
-                entries.get_mut(next_key).and_then(|next| next.at_mut(path))
+                for entry in entries {
+                    entry.retain_cards(keep);
+                }
             }
-            Self::Deck { .. } => None,
+            Self::Deck { cards, .. } => cards.retain(|card| keep(card)),
+        }
+    }
+
+    fn cards_mut(&mut self) -> &mut [Card] {
+        match self {
+            Self::Set { .. } => &mut [],
+            Self::Deck { cards, .. } => cards,
+        }
+    }
+
+    /// Every card beneath this node, in the order they appear in the tree.
+    pub fn cards(&self) -> Vec<&Card> {
+        match self {
+            Self::Set { entries, .. } => entries.iter().flat_map(|entry| entry.cards()).collect(),
+            Self::Deck { cards, .. } => cards.iter().collect(),
+        }
+    }
+
+    /// Every deck beneath (or at) this node.
+    pub fn decks(&self) -> Vec<&Self> {
+        match self {
+            Self::Set { entries, .. } => entries.iter().flat_map(|entry| entry.decks()).collect(),
+            Self::Deck { .. } => vec![self],
+        }
+    }
+
+    pub fn decks_mut(&mut self) -> Vec<&mut Self> {
+        match self {
+            Self::Set { entries, .. } => entries
+                .iter_mut()
+                .flat_map(|entry| entry.decks_mut())
+                .collect(),
+            Self::Deck { .. } => vec![self],
         }
     }
 
@@ -81,246 +378,462 @@ impl DeckNode {
                 name,
                 entries,
                 expanded,
+                ..
             } => {
                 let icon = if *expanded { "📂" } else { "📁" };
                 format!("{} {} ({})", icon, name, entries.len())
             }
-            Self::Deck { name, cards } => {
-                format!("📕 {} ({})", name, cards.len())
+            Self::Deck { name, cards, .. } => {
+                let suspended = cards.iter().filter(|card| card.suspended).count();
+                if suspended == 0 {
+                    format!("📕 {} ({})", name, cards.len())
+                } else {
+                    format!(
+                        "📕 {} ({} + {} suspended)",
+                        name,
+                        cards.len() - suspended,
+                        suspended
+                    )
+                }
             }
         }
     }
 
-    pub fn prompt_options(&self) -> Vec<NodePromptOption> {
-        fn build(this: &DeckNode, options: &mut Vec<NodePromptOption>, path: Vec<usize>) {
+    /// The entries of the main menu, each showing how many new cards and reviews are left to
+    /// study today according to `counts`.
+    pub fn prompt_options(&self, counts: &HashMap<Ulid, StudyCounts>) -> Vec<NodePromptOption> {
+        fn build(
+            this: &DeckNode,
+            counts: &HashMap<Ulid, StudyCounts>,
+            options: &mut Vec<NodePromptOption>,
+            path: Vec<Ulid>,
+        ) {
+            let name = match counts.get(&this.id()) {
+                Some(counts) => format!("{}  {}", this.display_name(), counts),
+                None => this.display_name(),
+            };
             options.push(NodePromptOption {
                 action: match this {
                     DeckNode::Set { .. } => NodeAction::ToggleExpanded,
                     DeckNode::Deck { .. } => NodeAction::Edit,
                 },
-                name: this.display_name(),
+                name,
                 path: DeckPath::new(path.clone()),
             });
 
-            match this {
-                DeckNode::Set {
-                    entries,
-                    expanded: true,
-                    ..
-                } => {
-                    for (i, child) in entries.iter().enumerate() {
-                        let mut new_path = path.clone();
-                        new_path.push(i);
-                        build(child, options, new_path);
-                    }
-                    options.push(NodePromptOption {
-                        action: NodeAction::Edit,
-                        name: "  ⚙️".to_owned(),
-                        path: DeckPath::new(path.clone()),
-                    });
+            if let DeckNode::Set {
+                entries,
+                expanded: true,
+                ..
+            } = this
+            {
+                for child in entries {
+                    let mut new_path = path.clone();
+                    new_path.push(child.id());
+                    build(child, counts, options, new_path);
                 }
-                _ => {}
+                options.push(NodePromptOption {
+                    action: NodeAction::Edit,
+                    name: "  ⚙️".to_owned(),
+                    path: DeckPath::new(path.clone()),
+                });
             }
         }
 
         let mut options: Vec<NodePromptOption> = Vec::new();
-        build(self, &mut options, Vec::new());
+        build(self, counts, &mut options, Vec::new());
         options
     }
 
-    pub fn prompt_select(&mut self, action: NodeAction) -> InquireResult<()> {
-        match action {
-            NodeAction::ToggleExpanded => {
-                let Self::Set { expanded, .. } = self else {
-                    panic!("ToggleExpanded is only applicable to Set's");
-                };
-                *expanded = !*expanded;
-                Ok(())
+    /// Asks the user to pick any node in the tree, regardless of which sets are expanded.
+    pub fn prompt_node(&self, message: &str) -> InquireResult<DeckPath> {
+        let path = self.prompt_node_matching(message, |_| true)?;
+        Ok(path.expect("the root itself is always an option"))
+    }
+
+    /// Asks the user to pick one of the nodes in the tree for which `filter` returns true,
+    /// or returns `None` if there are no such nodes.
+    pub fn prompt_node_matching(
+        &self,
+        message: &str,
+        filter: impl Fn(&DeckNode) -> bool,
+    ) -> InquireResult<Option<DeckPath>> {
+        fn build(
+            this: &DeckNode,
+            filter: &dyn Fn(&DeckNode) -> bool,
+            options: &mut Vec<NodePromptOption>,
+            path: Vec<Ulid>,
+        ) {
+            if filter(this) {
+                options.push(NodePromptOption {
+                    action: NodeAction::Edit,
+                    name: this.display_name(),
+                    path: DeckPath::new(path.clone()),
+                });
+            }
+            for child in this.entries() {
+                let mut new_path = path.clone();
+                new_path.push(child.id());
+                build(child, filter, options, new_path);
             }
-            NodeAction::Edit => self.prompt_edit(),
+        }
+
+        let mut options: Vec<NodePromptOption> = Vec::new();
+        build(self, &filter, &mut options, Vec::new());
+        if options.is_empty() {
+            return Ok(None);
+        }
+        Ok(Some(Select::new(message, options).prompt()?.path))
+    }
+
+    /// Removes the node with the given id from the set holding it, and returns it.
+    fn detach(&mut self, id: Ulid) -> Option<Self> {
+        let Self::Set { entries, .. } = self else {
+            return None;
+        };
+
+        match entries.iter().position(|entry| entry.id() == id) {
+            Some(index) => Some(entries.remove(index)),
+            None => entries.iter_mut().find_map(|entry| entry.detach(id)),
         }
     }
 
-    pub fn prompt_edit(&mut self) -> InquireResult<()> {
-        struct RemoveEntry {
+    /// Moves the node with the given id into the set with id `to`. Refuses, returning false,
+    /// if `to` isn't a set or lies within the node being moved.
+    pub fn move_node(&mut self, id: Ulid, to: Ulid) -> bool {
+        let Some(node) = self.find(id) else {
+            return false;
+        };
+        if node.find(to).is_some() || !matches!(self.find(to), Some(Self::Set { .. })) {
+            return false;
+        }
+
+        let Some(node) = self.detach(id) else {
+            return false;
+        };
+        let Some(Self::Set { entries, .. }) = self.find_mut(to) else {
+            unreachable!("checked to be a set above");
+        };
+        entries.push(node);
+        true
+    }
+
+    /// Moves the cards with the given ids into the deck with id `to`, returning how many
+    /// were moved.
+    pub fn move_cards(&mut self, ids: &[Ulid], to: Ulid) -> usize {
+        if !matches!(self.find(to), Some(Self::Deck { .. })) {
+            return 0;
+        }
+
+        let mut moved = Vec::new();
+        for &id in ids {
+            let Some(Self::Deck { cards, .. }) = self.deck_of_mut(id) else {
+                continue;
+            };
+            if let Some(index) = cards.iter().position(|card| card.id == id) {
+                moved.push(cards.remove(index));
+            }
+        }
+
+        let count = moved.len();
+        if let Some(Self::Deck { cards, .. }) = self.find_mut(to) {
+            cards.extend(moved);
+        }
+        count
+    }
+
+    /// Runs one step of the node's edit menu. Cards can be added from any of the `note_types`,
+    /// due `today`, and `preset` is the options preset the node uses.
+    pub fn prompt_edit(
+        &mut self,
+        note_types: &[NoteType],
+        preset: &Preset,
+        today: NaiveDate,
+    ) -> InquireResult<EditStep> {
+        struct ListEntry {
             index: usize,
             name: String,
         }
 
-        impl fmt::Display for RemoveEntry {
+        impl fmt::Display for ListEntry {
             fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
                 write!(f, "{}", self.name)
             }
         }
 
-        fn sort_removal(vec: &mut Vec<RemoveEntry>) {
+        fn sort_removal(vec: &mut [ListEntry]) {
             sort(vec, |a, b| a.index.cmp(&b.index));
         }
 
-        loop {
-            let display_name = &self.display_name();
-            match self {
-                Self::Set { name, entries, .. } => {
-                    enum Selection {
-                        AddDeck,
-                        AddSet,
-                        Rename,
-                        RemoveEntries,
-                        Back,
+        fn list<T>(items: &[T], name: impl Fn(&T) -> String) -> Vec<ListEntry> {
+            items
+                .iter()
+                .enumerate()
+                .map(|(i, x)| ListEntry {
+                    index: i,
+                    name: name(x),
+                })
+                .collect()
+        }
+
+        let display_name = &self.display_name();
+        match self {
+            Self::Set { name, entries, .. } => {
+                enum Selection {
+                    Study,
+                    AddDeck,
+                    AddSet,
+                    Rename,
+                    ReorderEntries,
+                    MoveEntry,
+                    RemoveEntries,
+                    Options,
+                    Optimize,
+                    Back,
+                }
+
+                impl fmt::Display for Selection {
+                    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                        write!(
+                            f,
+                            "{}",
+                            match self {
+                                Self::Study => STUDY,
+                                Self::AddDeck => ADD_DECK,
+                                Self::AddSet => ADD_SET,
+                                Self::Rename => RENAME,
+                                Self::ReorderEntries => REORDER_ENTRIES,
+                                Self::MoveEntry => MOVE_ENTRY,
+                                Self::RemoveEntries => REMOVE_ENTRIES,
+                                Self::Options => OPTIONS,
+                                Self::Optimize => OPTIMIZE,
+                                Self::Back => BACK,
+                            },
+                        )
                     }
+                }
 
-                    impl fmt::Display for Selection {
-                        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-                            write!(
-                                f,
-                                "{}",
-                                match self {
-                                    Self::AddDeck => ADD_DECK,
-                                    Self::AddSet => ADD_SET,
-                                    Self::Rename => RENAME,
-                                    Self::RemoveEntries => REMOVE_ENTRIES,
-                                    Self::Back => BACK,
-                                },
-                            )
-                        }
+                match Select::new(
+                    display_name,
+                    if entries.is_empty() {
+                        vec![
+                            Selection::AddDeck,
+                            Selection::AddSet,
+                            Selection::Rename,
+                            Selection::Options,
+                            Selection::Back,
+                        ]
+                    } else {
+                        vec![
+                            Selection::Study,
+                            Selection::AddDeck,
+                            Selection::AddSet,
+                            Selection::Rename,
+                            Selection::ReorderEntries,
+                            Selection::MoveEntry,
+                            Selection::RemoveEntries,
+                            Selection::Options,
+                            Selection::Optimize,
+                            Selection::Back,
+                        ]
+                    },
+                )
+                .prompt()?
+                {
+                    Selection::Study => Ok(EditStep::Study),
+                    Selection::Options => Ok(EditStep::Options),
+                    Selection::Optimize => Ok(EditStep::Optimize),
+                    Selection::AddDeck => {
+                        entries.push(Self::prompt_deck()?);
+                        Ok(EditStep::Changed)
+                    }
+                    Selection::AddSet => {
+                        entries.push(Self::prompt_set()?);
+                        Ok(EditStep::Changed)
+                    }
+                    Selection::Rename => {
+                        *name = prompt_rename(display_name)?;
+                        Ok(EditStep::Changed)
                     }
+                    Selection::ReorderEntries => {
+                        let options = list(entries, DeckNode::display_name);
+                        let entry = Select::new(ENTER_REORDER, options).prompt()?;
+                        let target = match Select::new(ENTER_DIRECTION, vec![MOVE_UP, MOVE_DOWN])
+                            .prompt()?
+                        {
+                            MOVE_UP => entry.index.checked_sub(1),
+                            _ => Some(entry.index + 1).filter(|i| *i < entries.len()),
+                        };
+
+                        let Some(target) = target else {
+                            return Ok(EditStep::Unchanged);
+                        };
+                        entries.swap(entry.index, target);
+                        Ok(EditStep::Changed)
+                    }
+                    Selection::MoveEntry => {
+                        let options = list(entries, DeckNode::display_name);
+                        let entry = Select::new(ENTER_MOVE, options).prompt()?;
+                        Ok(EditStep::MoveNode(entries[entry.index].id()))
+                    }
+                    Selection::RemoveEntries => {
+                        let options = list(entries, DeckNode::display_name);
 
-                    match Select::new(
-                        display_name,
-                        if entries.is_empty() {
-                            vec![
-                                Selection::AddDeck,
-                                Selection::AddSet,
-                                Selection::Rename,
-                                Selection::Back,
-                            ]
-                        } else {
-                            vec![
-                                Selection::AddDeck,
-                                Selection::AddSet,
-                                Selection::Rename,
-                                Selection::RemoveEntries,
-                                Selection::Back,
-                            ]
-                        },
-                    )
-                    .prompt()?
-                    {
-                        Selection::AddDeck => {
-                            entries.push(Self::prompt_deck()?);
+                        let mut to_remove = MultiSelect::new(ENTER_REMOVE, options).prompt()?;
+                        if to_remove.is_empty() {
+                            return Ok(EditStep::Unchanged);
                         }
-                        Selection::AddSet => {
-                            entries.push(Self::prompt_set()?);
+                        if !prompt_confirm()? {
+                            return Ok(EditStep::Unchanged);
                         }
-                        Selection::Rename => {
-                            *name = prompt_rename(&display_name)?;
-                        }
-                        Selection::RemoveEntries => {
-                            let options: Vec<RemoveEntry> = entries
-                                .iter()
-                                .enumerate()
-                                .map(|(i, x)| RemoveEntry {
-                                    index: i,
-                                    name: x.display_name(),
-                                })
-                                .collect();
-
-                            let mut to_remove = MultiSelect::new(ENTER_REMOVE, options).prompt()?;
-                            if to_remove.is_empty() {
-                                continue;
-                            }
-                            if !prompt_confirm()? {
-                                continue;
-                            }
-
-                            sort_removal(&mut to_remove);
-                            for entry in to_remove.into_iter().rev() {
-                                entries.remove(entry.index);
-                            }
+
+                        sort_removal(&mut to_remove);
+                        for entry in to_remove.into_iter().rev() {
+                            entries.remove(entry.index);
                         }
-                        Selection::Back => return Ok(()),
+                        Ok(EditStep::Changed)
+                    }
+                    Selection::Back => Ok(EditStep::Back),
+                }
+            }
+            Self::Deck {
+                name, cards, leech, ..
+            } => {
+                enum Selection {
+                    Study,
+                    AddCard,
+                    Rename,
+                    Options,
+                    LeechHandling,
+                    EditCards,
+                    MoveCards,
+                    RemoveCards,
+                    Optimize,
+                    Back,
+                }
+
+                impl fmt::Display for Selection {
+                    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                        write!(
+                            f,
+                            "{}",
+                            match self {
+                                Self::Study => STUDY,
+                                Self::AddCard => ADD_CARD,
+                                Self::Rename => RENAME,
+                                Self::Options => OPTIONS,
+                                Self::LeechHandling => LEECH_HANDLING,
+                                Self::EditCards => EDIT_CARDS,
+                                Self::MoveCards => MOVE_CARDS,
+                                Self::RemoveCards => REMOVE_CARDS,
+                                Self::Optimize => OPTIMIZE,
+                                Self::Back => BACK,
+                            },
+                        )
                     }
                 }
-                Self::Deck { name, cards, .. } => {
-                    enum Selection {
-                        AddCard,
-                        Rename,
-                        RemoveCards,
-                        Back,
+
+                let message = match &preset.scheduler {
+                    SchedulerKind::Leitner(leitner) => {
+                        format!("{} {}", display_name, leitner.display_boxes(cards.iter()))
                     }
+                    _ => display_name.clone(),
+                };
 
-                    impl fmt::Display for Selection {
-                        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-                            write!(
-                                f,
-                                "{}",
-                                match self {
-                                    Self::AddCard => ADD_CARD,
-                                    Self::Rename => RENAME,
-                                    Self::RemoveCards => REMOVE_CARDS,
-                                    Self::Back => BACK,
-                                },
-                            )
+                match Select::new(
+                    &message,
+                    if cards.is_empty() {
+                        vec![
+                            Selection::AddCard,
+                            Selection::Rename,
+                            Selection::Options,
+                            Selection::LeechHandling,
+                            Selection::Back,
+                        ]
+                    } else {
+                        let mut options = vec![
+                            Selection::Study,
+                            Selection::AddCard,
+                            Selection::Rename,
+                            Selection::Options,
+                            Selection::LeechHandling,
+                            Selection::EditCards,
+                            Selection::MoveCards,
+                            Selection::RemoveCards,
+                        ];
+                        if let SchedulerKind::Fsrs(_) = preset.scheduler {
+                            options.push(Selection::Optimize);
                         }
+                        options.push(Selection::Back);
+                        options
+                    },
+                )
+                .prompt()?
+                {
+                    Selection::Study => Ok(EditStep::Study),
+                    Selection::Optimize => Ok(EditStep::Optimize),
+                    Selection::AddCard => {
+                        cards.extend(Card::prompt_new(note_types, today)?);
+                        Ok(EditStep::Changed)
+                    }
+                    Selection::Rename => {
+                        *name = prompt_rename(display_name)?;
+                        Ok(EditStep::Changed)
+                    }
+                    Selection::Options => Ok(EditStep::Options),
+                    Selection::LeechHandling => {
+                        *leech = leech.prompt_new()?;
+                        Ok(EditStep::Changed)
                     }
+                    Selection::EditCards => {
+                        let options = list(cards, Card::display_name);
 
-                    match Select::new(
-                        display_name,
-                        if cards.is_empty() {
-                            vec![Selection::AddCard, Selection::Rename, Selection::Back]
-                        } else {
-                            vec![
-                                Selection::AddCard,
-                                Selection::Rename,
-                                Selection::RemoveCards,
-                                Selection::Back,
-                            ]
-                        },
-                    )
-                    .prompt()?
-                    {
-                        Selection::AddCard => {
-                            cards.push(Card::prompt_new()?);
+                        let entry = Select::new(ENTER_EDIT_CARD, options).prompt()?;
+                        Ok(EditStep::EditCard(cards[entry.index].id))
+                    }
+                    Selection::MoveCards => {
+                        let options = list(cards, Card::display_name);
+                        let to_move = MultiSelect::new(ENTER_MOVE, options).prompt()?;
+                        if to_move.is_empty() {
+                            return Ok(EditStep::Unchanged);
                         }
-                        Selection::Rename => {
-                            *name = prompt_rename(display_name)?;
+                        Ok(EditStep::MoveCards(
+                            to_move
+                                .into_iter()
+                                .map(|entry| cards[entry.index].id)
+                                .collect(),
+                        ))
+                    }
+                    Selection::RemoveCards => {
+                        let options = list(cards, Card::display_name);
+
+                        let to_remove = MultiSelect::new(ENTER_REMOVE, options).prompt()?;
+                        if to_remove.is_empty() {
+                            return Ok(EditStep::Unchanged);
                         }
-                        Selection::RemoveCards => {
-                            let options: Vec<RemoveEntry> = cards
-                                .iter()
-                                .enumerate()
-                                .map(|(i, x)| RemoveEntry {
-                                    index: i,
-                                    name: x.display_name(),
-                                })
-                                .collect();
-
-                            let mut to_remove = MultiSelect::new(ENTER_REMOVE, options).prompt()?;
-                            if to_remove.is_empty() {
-                                continue;
-                            }
-                            if !prompt_confirm()? {
-                                continue;
-                            }
-
-                            sort_removal(&mut to_remove);
-                            for entry in to_remove.into_iter().rev() {
-                                cards.remove(entry.index);
-                            }
+                        if !prompt_confirm()? {
+                            return Ok(EditStep::Unchanged);
                         }
-                        Selection::Back => return Ok(()),
+
+                        let ids = to_remove
+                            .iter()
+                            .map(|entry| cards[entry.index].id)
+                            .collect();
+                        Ok(EditStep::RemoveCards(ids))
                     }
+                    Selection::Back => Ok(EditStep::Back),
                 }
             }
         }
     }
 }
 
+/// The location of a node, as the ids of the nodes leading down to it from the root.
 #[derive(Clone, Debug)]
-pub struct DeckPath(pub VecDeque<usize>);
+pub struct DeckPath(pub VecDeque<Ulid>);
 
 impl DeckPath {
-    pub fn new(path: impl Into<VecDeque<usize>>) -> Self {
+    pub fn new(path: impl Into<VecDeque<Ulid>>) -> Self {
         Self(path.into())
     }
 }
@@ -0,0 +1,446 @@
+use std::fmt;
+
+use inquire::{validator::Validation, Select, Text};
+use serde::{Deserialize, Serialize};
+use ulid::Ulid;
+
+use crate::prelude::*;
+
+/// The placeholder a back template can use to include the rendered front.
+const FRONT_SIDE: &str = "FrontSide";
+
+/// A kind of note with named fields, and templates rendering those fields into cards.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct NoteType {
+    pub id: Ulid,
+    pub name: String,
+    pub fields: Vec<String>,
+    pub templates: Vec<Template>,
+}
+
+/// One card generated for every note of a type, with `{{Field}}` placeholders on each side.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Template {
+    pub id: Ulid,
+    pub name: String,
+    pub front: String,
+    pub back: String,
+}
+
+impl Template {
+    pub fn new(name: impl Into<String>, front: impl Into<String>, back: impl Into<String>) -> Self {
+        Self {
+            id: Ulid::new(),
+            name: name.into(),
+            front: front.into(),
+            back: back.into(),
+        }
+    }
+
+    /// The front and back of the card this template generates from the given field values.
+    pub fn render(&self, fields: &[String], values: &[String]) -> (String, String) {
+        let front = substitute(&self.front, fields, values, None);
+        let back = substitute(&self.back, fields, values, Some(&front));
+        (front, back)
+    }
+
+    /// The back this template generates from the given field values, leaving out the front
+    /// it repeats through `{{FrontSide}}`.
+    pub fn answer(&self, fields: &[String], values: &[String]) -> String {
+        substitute(&self.back, fields, values, Some(""))
+            .trim()
+            .to_owned()
+    }
+
+    /// Asks for the template's name and both of its sides, starting from their current text.
+    fn prompt_sides(&mut self) -> InquireResult<()> {
+        self.name = Text::new(ENTER_TEMPLATE_NAME)
+            .with_initial_value(&self.name)
+            .prompt()?;
+        self.front = Text::new(ENTER_TEMPLATE_FRONT)
+            .with_initial_value(&self.front)
+            .with_help_message(HELP_TEMPLATE_FRONT)
+            .prompt()?;
+        self.back = Text::new(ENTER_TEMPLATE_BACK)
+            .with_initial_value(&self.back)
+            .with_help_message(HELP_TEMPLATE_BACK)
+            .prompt()?;
+        Ok(())
+    }
+}
+
+/// Replaces every `{{Name}}` in `template` with the value of the field called `Name`, and
+/// `{{FrontSide}}` with `front_side` if given. Unknown placeholders render as nothing.
+fn substitute(
+    template: &str,
+    fields: &[String],
+    values: &[String],
+    front_side: Option<&str>,
+) -> String {
+    let mut out = String::new();
+    let mut rest = template;
+    while let Some(start) = rest.find("{{") {
+        let Some(len) = rest[start + 2..].find("}}") else {
+            break;
+        };
+        out.push_str(&rest[..start]);
+
+        let name = rest[start + 2..start + 2 + len].trim();
+        match (name, front_side) {
+            (FRONT_SIDE, Some(front)) => out.push_str(front),
+            _ => {
+                if let Some(value) = fields
+                    .iter()
+                    .position(|field| field == name)
+                    .and_then(|i| values.get(i))
+                {
+                    out.push_str(value);
+                }
+            }
+        }
+        rest = &rest[start + 2 + len + 2..];
+    }
+    out.push_str(rest);
+    out
+}
+
+/// `template` with every placeholder of the field `old` pointing at `new` instead, reading
+/// placeholders the same way `substitute` does.
+fn rename_placeholders(template: &str, old: &str, new: &str) -> String {
+    let mut out = String::new();
+    let mut rest = template;
+    while let Some(start) = rest.find("{{") {
+        let Some(len) = rest[start + 2..].find("}}") else {
+            break;
+        };
+        let placeholder = &rest[start..start + 2 + len + 2];
+        out.push_str(&rest[..start]);
+        if rest[start + 2..start + 2 + len].trim() == old {
+            out.push_str(&format!("{{{{{}}}}}", new));
+        } else {
+            out.push_str(placeholder);
+        }
+        rest = &rest[start + 2 + len + 2..];
+    }
+    out.push_str(rest);
+    out
+}
+
+impl NoteType {
+    pub fn new(
+        name: impl Into<String>,
+        fields: impl IntoIterator<Item = impl Into<String>>,
+        templates: impl IntoIterator<Item = Template>,
+    ) -> Self {
+        Self {
+            id: Ulid::new(),
+            name: name.into(),
+            fields: fields.into_iter().map(Into::into).collect(),
+            templates: templates.into_iter().collect(),
+        }
+    }
+
+    pub fn template(&self, id: Ulid) -> Option<&Template> {
+        self.templates.iter().find(|template| template.id == id)
+    }
+
+    /// One card for every template, sharing a new note holding `values`.
+    pub fn generate(&self, values: Vec<String>, today: NaiveDate) -> Vec<Card> {
+        let note = Some(Ulid::new());
+        self.templates
+            .iter()
+            .map(|template| self.card(template, note, values.clone(), today))
+            .collect()
+    }
+
+    /// A new card of the given template, belonging to `note`.
+    pub fn card(
+        &self,
+        template: &Template,
+        note: Option<Ulid>,
+        values: Vec<String>,
+        today: NaiveDate,
+    ) -> Card {
+        let mut card = Card {
+            kind: CardKind::Template {
+                note_type: self.id,
+                template: template.id,
+            },
+            note,
+            fields: values,
+            ..Card::new("", "", today)
+        };
+        self.render(&mut card);
+        card
+    }
+
+    /// Renders the front and back of `card` again from its field values, if it was generated
+    /// from this note type. Its schedule is left alone.
+    pub fn render(&self, card: &mut Card) {
+        let CardKind::Template {
+            note_type,
+            template,
+        } = card.kind
+        else {
+            return;
+        };
+        if note_type != self.id {
+            return;
+        }
+        if let Some(template) = self.template(template) {
+            (card.front, card.back) = template.render(&self.fields, &card.fields);
+        }
+    }
+
+    /// The back of `card` without its front, if it was generated from this note type.
+    pub fn answer(&self, card: &Card) -> Option<String> {
+        match card.kind {
+            CardKind::Template {
+                note_type,
+                template,
+            } if note_type == self.id => self
+                .template(template)
+                .map(|template| template.answer(&self.fields, &card.fields)),
+            _ => None,
+        }
+    }
+
+    pub fn display_name(&self) -> String {
+        format!(
+            "📝 {} ({} fields, {} cards)",
+            self.name,
+            self.fields.len(),
+            self.templates.len()
+        )
+    }
+
+    /// Asks for the name and fields of a new note type, along with its first template.
+    pub fn prompt_new() -> InquireResult<Self> {
+        let name = Text::new(ENTER_NOTE_TYPE_NAME).prompt()?;
+        let fields: Vec<String> = Text::new(ENTER_NOTE_FIELDS)
+            .with_help_message(HELP_NOTE_FIELDS)
+            .with_validator(|text: &str| {
+                Ok(match parse_fields(text) {
+                    Some(_) => Validation::Valid,
+                    None => Validation::Invalid(INVALID_NOTE_FIELDS.into()),
+                })
+            })
+            .prompt()
+            .map(|text| parse_fields(&text).unwrap_or_default())?;
+
+        let mut template = Template::new(
+            "Card 1",
+            format!("{{{{{}}}}}", fields[0]),
+            fields
+                .get(1)
+                .map(|field| format!("{{{{{}}}}}", field))
+                .unwrap_or_default(),
+        );
+        template.prompt_sides()?;
+        Ok(Self::new(name, fields, [template]))
+    }
+
+    /// Asks for the value of every field, starting from the `current` ones.
+    pub fn prompt_values(&self, current: &[String]) -> InquireResult<Vec<String>> {
+        self.fields
+            .iter()
+            .enumerate()
+            .map(|(i, field)| {
+                Text::new(&format!("{}:", field))
+                    .with_initial_value(current.get(i).map_or("", String::as_str))
+                    .prompt()
+            })
+            .collect()
+    }
+
+    pub fn prompt_edit(&mut self) -> InquireResult<EditStep> {
+        enum Selection {
+            Rename,
+            AddField,
+            RenameField,
+            AddTemplate,
+            EditTemplate,
+            Back,
+        }
+
+        impl fmt::Display for Selection {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                write!(
+                    f,
+                    "{}",
+                    match self {
+                        Self::Rename => RENAME,
+                        Self::AddField => ADD_FIELD,
+                        Self::RenameField => RENAME_FIELD,
+                        Self::AddTemplate => ADD_TEMPLATE,
+                        Self::EditTemplate => EDIT_TEMPLATE,
+                        Self::Back => BACK,
+                    }
+                )
+            }
+        }
+
+        let options = vec![
+            Selection::Rename,
+            Selection::AddField,
+            Selection::RenameField,
+            Selection::AddTemplate,
+            Selection::EditTemplate,
+            Selection::Back,
+        ];
+        match Select::new(&self.display_name(), options).prompt()? {
+            Selection::Rename => {
+                self.name = Text::new(&format!("{} ->", self.name))
+                    .with_initial_value(&self.name)
+                    .prompt()?;
+                Ok(EditStep::Changed)
+            }
+            Selection::AddField => {
+                let field = Text::new(ENTER_FIELD_NAME).prompt()?;
+                if field.is_empty() || self.fields.contains(&field) {
+                    return Ok(EditStep::Unchanged);
+                }
+                self.fields.push(field);
+                Ok(EditStep::Changed)
+            }
+            Selection::RenameField => {
+                let index = Select::new(ENTER_FIELD, self.fields.clone())
+                    .raw_prompt()?
+                    .index;
+                let old = self.fields[index].clone();
+                let new = Text::new(&format!("{} ->", old))
+                    .with_initial_value(&old)
+                    .prompt()?;
+                if new.is_empty() || self.fields.contains(&new) {
+                    return Ok(EditStep::Unchanged);
+                }
+
+                // Keep the templates pointing at the field under its new name
+                for template in &mut self.templates {
+                    template.front = rename_placeholders(&template.front, &old, &new);
+                    template.back = rename_placeholders(&template.back, &old, &new);
+                }
+                self.fields[index] = new;
+                Ok(EditStep::Changed)
+            }
+            Selection::AddTemplate => {
+                let mut template =
+                    Template::new(format!("Card {}", self.templates.len() + 1), "", "");
+                template.prompt_sides()?;
+                self.templates.push(template);
+                Ok(EditStep::Changed)
+            }
+            Selection::EditTemplate => {
+                let names = self.templates.iter().map(|t| t.name.clone()).collect();
+                let index = Select::new(ENTER_TEMPLATE, names).raw_prompt()?.index;
+                self.templates[index].prompt_sides()?;
+                Ok(EditStep::Changed)
+            }
+            Selection::Back => Ok(EditStep::Back),
+        }
+    }
+}
+
+/// The distinct, non-empty field names in a comma-separated list, or `None` if there are none
+/// or a name is repeated.
+fn parse_fields(text: &str) -> Option<Vec<String>> {
+    let fields: Vec<String> = text
+        .split(',')
+        .map(str::trim)
+        .filter(|field| !field.is_empty())
+        .map(str::to_owned)
+        .collect();
+    let distinct = fields
+        .iter()
+        .enumerate()
+        .all(|(i, field)| !fields[..i].contains(field));
+    (!fields.is_empty() && distinct).then_some(fields)
+}
+
+impl Collection {
+    /// Lists the note types, letting the user add new ones and edit existing ones.
+    pub fn prompt_note_types(&mut self) -> InquireResult<()> {
+        enum Selection {
+            NoteType(usize, String),
+            Add,
+            Back,
+        }
+
+        impl fmt::Display for Selection {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                match self {
+                    Self::NoteType(_, name) => write!(f, "{}", name),
+                    Self::Add => write!(f, "{}", ADD_NOTE_TYPE),
+                    Self::Back => write!(f, "{}", BACK),
+                }
+            }
+        }
+
+        loop {
+            let mut options: Vec<Selection> = self
+                .note_types
+                .iter()
+                .enumerate()
+                .map(|(i, note_type)| Selection::NoteType(i, note_type.display_name()))
+                .collect();
+            options.push(Selection::Add);
+            options.push(Selection::Back);
+
+            match Select::new(NOTE_TYPES, options).prompt()? {
+                Selection::NoteType(index, _) => self.prompt_edit_note_type(index)?,
+                Selection::Add => {
+                    self.note_types.push(NoteType::prompt_new()?);
+                    self.save()?;
+                }
+                Selection::Back => return Ok(()),
+            }
+        }
+    }
+
+    /// Runs the editor of the note type at `index`, bringing the cards generated from it up to
+    /// date and saving after every change.
+    fn prompt_edit_note_type(&mut self, index: usize) -> InquireResult<()> {
+        loop {
+            match self.note_types[index].prompt_edit()? {
+                EditStep::Changed => {
+                    let today = self.today();
+                    self.root.sync_note_type(&self.note_types[index], today);
+                    self.save()?;
+                }
+                EditStep::Back => return Ok(()),
+                _ => {}
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fields(names: &[&str]) -> Vec<String> {
+        names.iter().map(|name| name.to_string()).collect()
+    }
+
+    #[test]
+    fn answer_leaves_out_the_front_side() {
+        let template = Template::new("Card 1", "{{Word}}", "{{FrontSide}}\n\n{{Meaning}}");
+        let (fields, values) = (fields(&["Word", "Meaning"]), fields(&["chat", "cat"]));
+        assert_eq!(template.answer(&fields, &values), "cat");
+    }
+
+    #[test]
+    fn answer_keeps_text_matching_the_front() {
+        let template = Template::new("Card 1", "{{Word}}", "{{Meaning}}");
+        let (fields, values) = (fields(&["Word", "Meaning"]), fields(&["a", "has"]));
+        assert_eq!(template.answer(&fields, &values), "has");
+    }
+
+    #[test]
+    fn renames_placeholders_with_spaces() {
+        assert_eq!(
+            rename_placeholders("{{ Word }} / {{Word}} / {{Other}}", "Word", "Term"),
+            "{{Term}} / {{Term}} / {{Other}}"
+        );
+    }
+}
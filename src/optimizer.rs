@@ -0,0 +1,246 @@
+use ulid::Ulid;
+
+use crate::prelude::*;
+
+/// The fewest reviews with a known outcome worth fitting FSRS weights to.
+pub const OPTIMIZE_MINIMUM_REVIEWS: usize = 50;
+
+const ITERATIONS: usize = 300;
+const LEARNING_RATE: f64 = 0.04;
+const EPSILON: f64 = 1e-4;
+
+/// The range each FSRS weight is kept within while optimizing, so the model stays sensible.
+const WEIGHT_BOUNDS: [(f64, f64); 17] = [
+    (0.1, 100.0),
+    (0.1, 100.0),
+    (0.1, 100.0),
+    (0.1, 100.0),
+    (1.0, 10.0),
+    (0.1, 5.0),
+    (0.1, 5.0),
+    (0.0, 0.75),
+    (0.0, 4.0),
+    (0.0, 0.8),
+    (0.01, 3.0),
+    (0.5, 5.0),
+    (0.01, 0.2),
+    (0.01, 0.9),
+    (0.01, 3.0),
+    (0.0, 1.0),
+    (1.0, 6.0),
+];
+
+/// How well a set of FSRS weights predicts the outcomes of past reviews.
+#[derive(Clone, Copy, Debug)]
+pub struct Evaluation {
+    /// The mean negative log-likelihood of each review's outcome.
+    pub log_loss: f64,
+    /// The root mean squared difference between predicted recall and actual outcome.
+    pub rmse: f64,
+}
+
+#[derive(Clone, Debug)]
+pub struct Optimization {
+    pub weights: [f64; 17],
+    pub reviews: usize,
+    pub before: Evaluation,
+    pub after: Evaluation,
+}
+
+impl Optimization {
+    pub fn improved(&self) -> bool {
+        self.after.log_loss < self.before.log_loss
+    }
+}
+
+impl Fsrs {
+    /// Replays the review history of every card, predicting the chance of recalling it at
+    /// each review from the ones before it.
+    pub fn evaluate(&self, histories: &[Vec<Review>]) -> Option<Evaluation> {
+        let mut count = 0;
+        let mut log_loss = 0.0;
+        let mut squared_error = 0.0;
+
+        for reviews in histories {
+            let mut memory: Option<MemoryState> = None;
+            let mut last_review: Option<NaiveDate> = None;
+
+            for review in reviews {
+                let elapsed_days = last_review.map_or(0, |last| (review.date - last).num_days());
+                if let Some(state) = memory.filter(|_| elapsed_days > 0) {
+                    let recall = Self::retrievability(elapsed_days as f64, state.stability)
+                        .clamp(1e-6, 1.0 - 1e-6);
+                    let recalled = if review.grade == Grade::Again {
+                        0.0
+                    } else {
+                        1.0
+                    };
+
+                    log_loss -= recalled * recall.ln() + (1.0 - recalled) * (1.0 - recall).ln();
+                    squared_error += (recall - recalled).powi(2);
+                    count += 1;
+                }
+
+                memory = Some(self.next_memory(memory, elapsed_days as f64, review.grade));
+                last_review = Some(review.date);
+            }
+        }
+
+        (count > 0).then(|| Evaluation {
+            log_loss: log_loss / count as f64,
+            rmse: (squared_error / count as f64).sqrt(),
+        })
+    }
+
+    fn review_count(histories: &[Vec<Review>]) -> usize {
+        histories
+            .iter()
+            .map(|reviews| {
+                reviews
+                    .windows(2)
+                    .filter(|pair| pair[1].date > pair[0].date)
+                    .count()
+            })
+            .sum()
+    }
+
+    /// Fits the weights to the review `histories` of a set of cards by gradient descent on
+    /// log-loss, starting from the current weights. Returns `None` if there are too few
+    /// reviews.
+    pub fn optimize(&self, histories: &[Vec<Review>]) -> Option<Optimization> {
+        let reviews = Self::review_count(histories);
+        if reviews < OPTIMIZE_MINIMUM_REVIEWS {
+            return None;
+        }
+
+        let before = self.evaluate(histories)?;
+        let mut current = self.clone();
+        let mut best = (self.weights, before);
+
+        // Adam keeps a running average of each weight's gradient and its magnitude, which
+        // copes with weights that live on very different scales.
+        let mut m = [0.0; 17];
+        let mut v = [0.0; 17];
+        let (beta1, beta2): (f64, f64) = (0.9, 0.999);
+
+        for step in 1..=ITERATIONS {
+            let gradient = current.gradient(histories);
+            for i in 0..17 {
+                m[i] = beta1 * m[i] + (1.0 - beta1) * gradient[i];
+                v[i] = beta2 * v[i] + (1.0 - beta2) * gradient[i].powi(2);
+                let m_hat = m[i] / (1.0 - beta1.powi(step as i32));
+                let v_hat = v[i] / (1.0 - beta2.powi(step as i32));
+
+                let (low, high) = WEIGHT_BOUNDS[i];
+                current.weights[i] = (current.weights[i]
+                    - LEARNING_RATE * m_hat / (v_hat.sqrt() + 1e-8))
+                    .clamp(low, high);
+            }
+
+            if let Some(evaluation) = current.evaluate(histories) {
+                if evaluation.log_loss < best.1.log_loss {
+                    best = (current.weights, evaluation);
+                }
+            }
+        }
+
+        Some(Optimization {
+            weights: best.0,
+            reviews,
+            before,
+            after: best.1,
+        })
+    }
+
+    /// The gradient of the log-loss with respect to each weight, by central differences.
+    fn gradient(&self, histories: &[Vec<Review>]) -> [f64; 17] {
+        let mut gradient = [0.0; 17];
+        let mut probe = self.clone();
+
+        for (i, slope) in gradient.iter_mut().enumerate() {
+            let weight = self.weights[i];
+
+            probe.weights[i] = weight + EPSILON;
+            let above = probe.evaluate(histories).map_or(0.0, |e| e.log_loss);
+            probe.weights[i] = weight - EPSILON;
+            let below = probe.evaluate(histories).map_or(0.0, |e| e.log_loss);
+            probe.weights[i] = weight;
+
+            *slope = (above - below) / (2.0 * EPSILON);
+        }
+
+        gradient
+    }
+}
+
+impl Collection {
+    /// Fits FSRS weights for every FSRS preset used beneath the node with the given id, each
+    /// to the logged review history of the cards in all the decks using that preset, reports
+    /// how much better they predict it, and writes them into the preset. Returns whether
+    /// anything was changed.
+    pub fn prompt_optimize(&mut self, id: Ulid) -> InquireResult<bool> {
+        let Some(node) = self.root.find(id) else {
+            return Ok(false);
+        };
+
+        let mut presets: Vec<Ulid> = Vec::new();
+        for deck in node.decks() {
+            let preset = self.preset_for(deck.id());
+            if matches!(preset.scheduler, SchedulerKind::Fsrs(_)) && !presets.contains(&preset.id) {
+                presets.push(preset.id);
+            }
+        }
+        if presets.is_empty() {
+            println!("{}", NO_FSRS_DECKS);
+            return Ok(false);
+        }
+
+        let mut changed = false;
+        for id in presets {
+            let preset = self.preset(Some(id));
+            let SchedulerKind::Fsrs(start) = &preset.scheduler else {
+                continue;
+            };
+            println!("{}", preset.name);
+
+            // Presets are shared, so fit to every deck using this one, not just those here
+            let cards: Vec<&Card> = self
+                .root
+                .decks()
+                .into_iter()
+                .filter(|deck| self.preset_for(deck.id()).id == id)
+                .flat_map(|deck| deck.cards())
+                .collect();
+            let histories = self.reviews_of(&cards);
+            let Some(optimization) = start.optimize(&histories) else {
+                println!("{}", NOT_ENOUGH_REVIEWS);
+                continue;
+            };
+
+            println!(
+                concat!(
+                    "{} reviews\n         log-loss  RMSE\n",
+                    " before  {:.4}    {:.4}\n after   {:.4}    {:.4}",
+                ),
+                optimization.reviews,
+                optimization.before.log_loss,
+                optimization.before.rmse,
+                optimization.after.log_loss,
+                optimization.after.rmse,
+            );
+
+            if !optimization.improved() {
+                println!("{}", OPTIMIZE_NO_IMPROVEMENT);
+                continue;
+            }
+
+            let preset = self.presets.iter_mut().find(|preset| preset.id == id);
+            if let Some(SchedulerKind::Fsrs(fsrs)) = preset.map(|preset| &mut preset.scheduler) {
+                fsrs.weights = optimization.weights;
+            }
+            println!("{}", OPTIMIZE_SAVED);
+            changed = true;
+        }
+        Ok(changed)
+    }
+}
@@ -0,0 +1,394 @@
+use std::fmt;
+
+use chrono::Duration;
+use inquire::{validator::Validation, CustomType, Select, Text};
+use serde::{Deserialize, Serialize};
+use ulid::Ulid;
+
+use crate::prelude::*;
+
+pub const PRESET_DEFAULT_NEW_PER_DAY: u32 = 20;
+pub const PRESET_DEFAULT_REVIEWS_PER_DAY: u32 = 200;
+pub const PRESET_DEFAULT_LEARNING_STEPS: [u32; 2] = [1, 10];
+pub const PRESET_DEFAULT_RELEARNING_STEPS: [u32; 1] = [10];
+pub const PRESET_DEFAULT_MAXIMUM_INTERVAL: u32 = 36500;
+
+/// A named group of options controlling how the decks using it are studied and scheduled.
+/// Decks and sets either have a preset assigned or use the one of the set they are in.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Preset {
+    pub id: Ulid,
+    pub name: String,
+    /// The most new cards to introduce each day.
+    pub new_per_day: u32,
+    /// The most reviews of already-seen cards to show each day.
+    pub reviews_per_day: u32,
+    /// The delays, in minutes, a new card is shown again after before it graduates.
+    pub learning_steps: Vec<u32>,
+    /// The delays, in minutes, a forgotten card is shown again after before it graduates
+    /// again.
+    #[serde(default = "default_relearning_steps")]
+    pub relearning_steps: Vec<u32>,
+    /// The interval, in days, a card gets once it has graduated from learning.
+    pub graduating_interval: u32,
+    /// The extra multiplier applied to the interval of cards graded Easy.
+    pub easy_bonus: f64,
+    /// The longest interval, in days, any card is given.
+    pub maximum_interval: u32,
+    /// The chance of remembering a card FSRS aims for.
+    pub desired_retention: f64,
+    pub scheduler: SchedulerKind,
+}
+
+impl Default for Preset {
+    fn default() -> Self {
+        Self {
+            id: Ulid::new(),
+            name: DEFAULT_PRESET_NAME.to_owned(),
+            new_per_day: PRESET_DEFAULT_NEW_PER_DAY,
+            reviews_per_day: PRESET_DEFAULT_REVIEWS_PER_DAY,
+            learning_steps: PRESET_DEFAULT_LEARNING_STEPS.to_vec(),
+            relearning_steps: default_relearning_steps(),
+            graduating_interval: 1,
+            easy_bonus: SM2_EASY_BONUS,
+            maximum_interval: PRESET_DEFAULT_MAXIMUM_INTERVAL,
+            desired_retention: FSRS_DEFAULT_RETENTION,
+            scheduler: SchedulerKind::default(),
+        }
+    }
+}
+
+fn default_relearning_steps() -> Vec<u32> {
+    PRESET_DEFAULT_RELEARNING_STEPS.to_vec()
+}
+
+impl Scheduler for Preset {
+    /// Schedules the card with the preset's algorithm, tuned by its options, and keeps the
+    /// new interval within the maximum.
+    fn schedule(&self, card: &mut Card, grade: Grade, today: NaiveDate) {
+        match &self.scheduler {
+            SchedulerKind::Sm2 => Sm2 {
+                graduating_interval: self.graduating_interval,
+                easy_bonus: self.easy_bonus,
+            }
+            .schedule(card, grade, today),
+            SchedulerKind::Fsrs(fsrs) => fsrs.schedule(card, grade, today, self.desired_retention),
+            SchedulerKind::Leitner(leitner) => leitner.schedule(card, grade, today),
+        }
+
+        if card.interval > self.maximum_interval {
+            card.interval = self.maximum_interval;
+            card.due = today + Duration::days(card.interval as i64);
+        }
+    }
+}
+
+impl fmt::Display for Preset {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let steps = |steps: &[u32]| {
+            let steps: Vec<String> = steps.iter().map(|step| format!("{}m", step)).collect();
+            steps.join(" ")
+        };
+
+        writeln!(f, "⚙️ {}", self.name)?;
+        writeln!(f, "  New cards/day       {}", self.new_per_day)?;
+        writeln!(f, "  Maximum reviews/day {}", self.reviews_per_day)?;
+        writeln!(f, "  Learning steps      {}", steps(&self.learning_steps))?;
+        writeln!(f, "  Relearning steps    {}", steps(&self.relearning_steps))?;
+        writeln!(f, "  Graduating interval {}d", self.graduating_interval)?;
+        writeln!(f, "  Easy bonus          {:.2}", self.easy_bonus)?;
+        writeln!(f, "  Maximum interval    {}d", self.maximum_interval)?;
+        writeln!(
+            f,
+            "  Desired retention   {:.0}%",
+            self.desired_retention * 100.0
+        )?;
+        write!(f, "  Scheduler           {}", self.scheduler)
+    }
+}
+
+impl Preset {
+    pub fn prompt_edit(&mut self) -> InquireResult<EditStep> {
+        enum Selection {
+            Rename,
+            NewPerDay,
+            ReviewsPerDay,
+            LearningSteps,
+            RelearningSteps,
+            GraduatingInterval,
+            EasyBonus,
+            MaximumInterval,
+            DesiredRetention,
+            Scheduler,
+            Back,
+        }
+
+        impl fmt::Display for Selection {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                write!(
+                    f,
+                    "{}",
+                    match self {
+                        Self::Rename => RENAME,
+                        Self::NewPerDay => NEW_PER_DAY,
+                        Self::ReviewsPerDay => REVIEWS_PER_DAY,
+                        Self::LearningSteps => LEARNING_STEPS,
+                        Self::RelearningSteps => RELEARNING_STEPS,
+                        Self::GraduatingInterval => GRADUATING_INTERVAL,
+                        Self::EasyBonus => EASY_BONUS,
+                        Self::MaximumInterval => MAXIMUM_INTERVAL,
+                        Self::DesiredRetention => DESIRED_RETENTION,
+                        Self::Scheduler => SCHEDULER,
+                        Self::Back => BACK,
+                    }
+                )
+            }
+        }
+
+        let options = vec![
+            Selection::Rename,
+            Selection::NewPerDay,
+            Selection::ReviewsPerDay,
+            Selection::LearningSteps,
+            Selection::RelearningSteps,
+            Selection::GraduatingInterval,
+            Selection::EasyBonus,
+            Selection::MaximumInterval,
+            Selection::DesiredRetention,
+            Selection::Scheduler,
+            Selection::Back,
+        ];
+        match Select::new(&self.to_string(), options).prompt()? {
+            Selection::Rename => {
+                self.name = Text::new(&format!("{} ->", self.name))
+                    .with_initial_value(&self.name)
+                    .prompt()?;
+            }
+            Selection::NewPerDay => {
+                self.new_per_day = CustomType::new(&format!("{}:", NEW_PER_DAY))
+                    .with_default(self.new_per_day)
+                    .prompt()?;
+            }
+            Selection::ReviewsPerDay => {
+                self.reviews_per_day = CustomType::new(&format!("{}:", REVIEWS_PER_DAY))
+                    .with_default(self.reviews_per_day)
+                    .prompt()?;
+            }
+            Selection::LearningSteps => {
+                self.learning_steps = prompt_steps(
+                    ENTER_LEARNING_STEPS,
+                    HELP_LEARNING_STEPS,
+                    &self.learning_steps,
+                )?;
+            }
+            Selection::RelearningSteps => {
+                self.relearning_steps = prompt_steps(
+                    ENTER_RELEARNING_STEPS,
+                    HELP_RELEARNING_STEPS,
+                    &self.relearning_steps,
+                )?;
+            }
+            Selection::GraduatingInterval => {
+                self.graduating_interval = CustomType::new(&format!("{}:", GRADUATING_INTERVAL))
+                    .with_default(self.graduating_interval)
+                    .with_validator(|days: &u32| Ok(at_least_one(*days)))
+                    .prompt()?;
+            }
+            Selection::EasyBonus => {
+                self.easy_bonus = CustomType::new(&format!("{}:", EASY_BONUS))
+                    .with_default(self.easy_bonus)
+                    .with_validator(|bonus: &f64| {
+                        Ok(if *bonus >= 1.0 {
+                            Validation::Valid
+                        } else {
+                            Validation::Invalid(INVALID_EASY_BONUS.into())
+                        })
+                    })
+                    .prompt()?;
+            }
+            Selection::MaximumInterval => {
+                self.maximum_interval = CustomType::new(&format!("{}:", MAXIMUM_INTERVAL))
+                    .with_default(self.maximum_interval)
+                    .with_validator(|days: &u32| Ok(at_least_one(*days)))
+                    .prompt()?;
+            }
+            Selection::DesiredRetention => {
+                self.desired_retention = CustomType::new(ENTER_DESIRED_RETENTION)
+                    .with_default(self.desired_retention)
+                    .with_help_message(HELP_DESIRED_RETENTION)
+                    .with_validator(|retention: &f64| {
+                        Ok(if (0.7..=0.99).contains(retention) {
+                            Validation::Valid
+                        } else {
+                            Validation::Invalid(INVALID_DESIRED_RETENTION.into())
+                        })
+                    })
+                    .prompt()?;
+            }
+            Selection::Scheduler => self.scheduler = self.scheduler.prompt_new()?,
+            Selection::Back => return Ok(EditStep::Back),
+        }
+        Ok(EditStep::Changed)
+    }
+}
+
+fn at_least_one(days: u32) -> Validation {
+    if days >= 1 {
+        Validation::Valid
+    } else {
+        Validation::Invalid(INVALID_DAYS.into())
+    }
+}
+
+fn prompt_steps(message: &str, help: &str, current: &[u32]) -> InquireResult<Vec<u32>> {
+    let current: Vec<String> = current.iter().map(u32::to_string).collect();
+    let input = Text::new(message)
+        .with_default(&current.join(", "))
+        .with_help_message(help)
+        .with_validator(|input: &str| {
+            Ok(match parse_steps(input) {
+                Some(_) => Validation::Valid,
+                None => Validation::Invalid(INVALID_LEARNING_STEPS.into()),
+            })
+        })
+        .prompt()?;
+    Ok(parse_steps(&input).unwrap_or_default())
+}
+
+/// A list of whole numbers of minutes separated by commas, which may be empty.
+fn parse_steps(input: &str) -> Option<Vec<u32>> {
+    input
+        .split(',')
+        .map(str::trim)
+        .filter(|step| !step.is_empty())
+        .map(|step| step.trim_end_matches('m').parse().ok().filter(|m| *m > 0))
+        .collect()
+}
+
+impl Collection {
+    /// The preset with the given id, or the default preset if there is none.
+    pub fn preset(&self, id: Option<Ulid>) -> &Preset {
+        id.and_then(|id| self.presets.iter().find(|preset| preset.id == id))
+            .unwrap_or(&self.presets[0])
+    }
+
+    /// The preset the node with the given id uses, whether assigned or inherited.
+    pub fn preset_for(&self, id: Ulid) -> &Preset {
+        self.preset(self.root.preset_of(id))
+    }
+
+    /// Lets the user pick which preset the node at `path` uses, create a new one for it, or
+    /// edit the one it uses.
+    pub fn prompt_node_options(&mut self, path: DeckPath) -> InquireResult<()> {
+        enum Selection {
+            Inherit,
+            Preset(usize, String),
+            NewPreset,
+            EditPreset,
+            Back,
+        }
+
+        impl fmt::Display for Selection {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                match self {
+                    Self::Inherit => write!(f, "{}", INHERIT_PRESET),
+                    Self::Preset(_, name) => write!(f, "{} {}", USE_PRESET, name),
+                    Self::NewPreset => write!(f, "{}", NEW_PRESET),
+                    Self::EditPreset => write!(f, "{}", EDIT_PRESET),
+                    Self::Back => write!(f, "{}", BACK),
+                }
+            }
+        }
+
+        loop {
+            let Some(node) = self.root.at(path.clone()) else {
+                return Ok(());
+            };
+            let id = node.id();
+            let assigned = node.preset();
+            let preset = self.preset_for(id);
+            let users = self.preset_users(preset.id);
+
+            let mut options = Vec::new();
+            if assigned.is_some() && id != self.root.id() {
+                options.push(Selection::Inherit);
+            }
+            options.extend(
+                self.presets
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, other)| other.id != preset.id)
+                    .map(|(i, other)| Selection::Preset(i, other.name.clone())),
+            );
+            options.push(Selection::NewPreset);
+            options.push(Selection::EditPreset);
+            options.push(Selection::Back);
+
+            let message = format!(
+                "{}\n{}\n  {}, {} {}",
+                node.display_name(),
+                preset,
+                match assigned {
+                    Some(_) => PRESET_ASSIGNED,
+                    None => PRESET_INHERITED,
+                },
+                users,
+                PRESET_DECKS
+            );
+            match Select::new(&message, options).prompt()? {
+                Selection::Inherit => self.set_preset(id, None),
+                Selection::Preset(index, _) => {
+                    let preset = self.presets[index].id;
+                    self.set_preset(id, Some(preset));
+                }
+                Selection::NewPreset => {
+                    let name = Text::new(ENTER_PRESET_NAME).prompt()?;
+                    let preset = Preset {
+                        id: Ulid::new(),
+                        name,
+                        ..preset.clone()
+                    };
+                    let preset_id = preset.id;
+                    self.presets.push(preset);
+                    self.set_preset(id, Some(preset_id));
+                }
+                Selection::EditPreset => {
+                    let preset_id = preset.id;
+                    self.prompt_edit_preset(preset_id)?;
+                    continue;
+                }
+                Selection::Back => return Ok(()),
+            }
+            self.save()?;
+        }
+    }
+
+    /// Runs the editor of the preset with the given id, saving after every change.
+    fn prompt_edit_preset(&mut self, id: Ulid) -> InquireResult<()> {
+        loop {
+            let Some(preset) = self.presets.iter_mut().find(|preset| preset.id == id) else {
+                return Ok(());
+            };
+            match preset.prompt_edit()? {
+                EditStep::Changed => self.save()?,
+                EditStep::Back => return Ok(()),
+                _ => {}
+            }
+        }
+    }
+
+    fn set_preset(&mut self, id: Ulid, preset: Option<Ulid>) {
+        if let Some(node) = self.root.find_mut(id) {
+            node.set_preset(preset);
+        }
+    }
+
+    /// The number of decks using the preset with the given id.
+    fn preset_users(&self, id: Ulid) -> usize {
+        self.root
+            .decks()
+            .into_iter()
+            .filter(|deck| self.preset_for(deck.id()).id == id)
+            .count()
+    }
+}
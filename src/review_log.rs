@@ -0,0 +1,114 @@
+use std::{
+    collections::HashMap,
+    fs::{self, OpenOptions},
+    io::{self, BufRead, BufReader, Write},
+    path::PathBuf,
+};
+
+use serde::{Deserialize, Serialize};
+use ulid::Ulid;
+
+use crate::prelude::*;
+
+const REVIEW_LOG_FILE: &str = "reviews.jsonl";
+
+/// A record of a single review, written when it happens and never changed afterwards.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ReviewLogEntry {
+    pub card: Ulid,
+    pub time: DateTime<Utc>,
+    pub grade: Grade,
+    /// How long the user took to answer, from being shown the front to grading the card.
+    pub duration_ms: u64,
+    pub previous_interval: u32,
+    pub interval: u32,
+    pub previous_ease: f64,
+    pub ease: f64,
+}
+
+/// The append-only log of every review in a collection, stored next to it as one JSON
+/// object per line.
+#[derive(Clone, Debug)]
+pub struct ReviewLog {
+    path: PathBuf,
+}
+
+impl ReviewLog {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+
+    pub fn append(&self, entry: &ReviewLogEntry) -> io::Result<()> {
+        if let Some(dir) = self.path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+
+        let mut line = serde_json::to_vec(entry)?;
+        line.push(b'\n');
+
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)?;
+        file.write_all(&line)?;
+        file.sync_all()
+    }
+
+    /// Reads every entry in the log, oldest first.
+    pub fn load(&self) -> io::Result<Vec<ReviewLogEntry>> {
+        let file = match fs::File::open(&self.path) {
+            Ok(file) => file,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(err) => return Err(err),
+        };
+
+        let mut entries = Vec::new();
+        for line in BufReader::new(file).lines() {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            entries.push(serde_json::from_str(&line)?);
+        }
+        Ok(entries)
+    }
+}
+
+impl Collection {
+    /// The review log stored alongside this collection.
+    pub fn review_log(&self) -> ReviewLog {
+        ReviewLog::new(self.path().with_file_name(REVIEW_LOG_FILE))
+    }
+
+    /// Every review in the collection's log, oldest first. This is the only record of past
+    /// reviews, which stats, daily limits and the optimizer are all built on.
+    pub fn history(&self) -> &[ReviewLogEntry] {
+        &self.history
+    }
+
+    /// Appends `entry` to the review log, and to the history once it is safely written.
+    pub fn record_review(&mut self, entry: ReviewLogEntry) -> io::Result<()> {
+        self.review_log().append(&entry)?;
+        self.history.push(entry);
+        Ok(())
+    }
+
+    /// The reviews of each of `cards` in the log, oldest first, on the study days they
+    /// happened.
+    pub fn reviews_of(&self, cards: &[&Card]) -> Vec<Vec<Review>> {
+        let mut reviews: HashMap<Ulid, Vec<Review>> =
+            cards.iter().map(|card| (card.id, Vec::new())).collect();
+        for entry in &self.history {
+            if let Some(card_reviews) = reviews.get_mut(&entry.card) {
+                card_reviews.push(Review {
+                    date: self.day.day_of(entry.time),
+                    grade: entry.grade,
+                });
+            }
+        }
+        cards
+            .iter()
+            .filter_map(|card| reviews.remove(&card.id))
+            .collect()
+    }
+}
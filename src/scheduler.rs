@@ -0,0 +1,234 @@
+use std::fmt;
+
+use chrono::Duration;
+use inquire::Select;
+use serde::{Deserialize, Serialize};
+
+use crate::prelude::*;
+
+pub const SM2_INITIAL_EASE: f64 = 2.5;
+pub const SM2_MINIMUM_EASE: f64 = 1.3;
+pub const SM2_HARD_MULTIPLIER: f64 = 1.2;
+pub const SM2_EASY_BONUS: f64 = 1.3;
+
+/// How well the user remembered a card when reviewing it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Grade {
+    Again,
+    Hard,
+    Good,
+    Easy,
+}
+
+impl Grade {
+    pub const ALL: [Grade; 4] = [Grade::Again, Grade::Hard, Grade::Good, Grade::Easy];
+
+    /// The response quality SM-2 assigns to this grade, from 0 to 5.
+    fn quality(self) -> f64 {
+        match self {
+            Self::Again => 0.0,
+            Self::Hard => 3.0,
+            Self::Good => 4.0,
+            Self::Easy => 5.0,
+        }
+    }
+}
+
+impl fmt::Display for Grade {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                Self::Again => AGAIN,
+                Self::Hard => HARD,
+                Self::Good => GOOD,
+                Self::Easy => EASY,
+            }
+        )
+    }
+}
+
+/// An algorithm deciding when a card should next be reviewed.
+pub trait Scheduler {
+    /// Updates the card's scheduling state and due date after reviewing it on `today`.
+    fn schedule(&self, card: &mut Card, grade: Grade, today: NaiveDate);
+}
+
+/// The scheduling algorithm used for the cards of a deck.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub enum SchedulerKind {
+    #[default]
+    Sm2,
+    Fsrs(Fsrs),
+    Leitner(Leitner),
+}
+
+impl SchedulerKind {
+    pub fn prompt_new(&self) -> InquireResult<Self> {
+        let options = vec![SCHEDULER_SM2, SCHEDULER_FSRS, SCHEDULER_LEITNER];
+        let cursor = match self {
+            Self::Sm2 => 0,
+            Self::Fsrs(_) => 1,
+            Self::Leitner(_) => 2,
+        };
+
+        match Select::new(ENTER_SCHEDULER, options)
+            .with_starting_cursor(cursor)
+            .prompt()?
+        {
+            SCHEDULER_FSRS => Ok(Self::Fsrs(match self {
+                Self::Fsrs(fsrs) => fsrs.clone(),
+                _ => Fsrs::default(),
+            })),
+            SCHEDULER_LEITNER => {
+                let mut leitner = match self {
+                    Self::Leitner(leitner) => leitner.clone(),
+                    _ => Leitner::default(),
+                };
+                leitner.intervals = leitner.prompt_intervals()?;
+                Ok(Self::Leitner(leitner))
+            }
+            _ => Ok(Self::Sm2),
+        }
+    }
+}
+
+impl fmt::Display for SchedulerKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Sm2 => write!(f, "{}", SCHEDULER_SM2),
+            Self::Fsrs(_) => write!(f, "{}", SCHEDULER_FSRS),
+            Self::Leitner(leitner) => write!(
+                f,
+                "{} ({} boxes)",
+                SCHEDULER_LEITNER,
+                leitner.intervals().len()
+            ),
+        }
+    }
+}
+
+/// The SM-2 algorithm.
+///
+/// Forgotten cards lose ease, start over from their first interval and stay due today so
+/// they are shown again. Remembered cards are pushed out by the graduating interval, then 6
+/// days, then by their previous interval multiplied by their ease.
+#[derive(Clone, Copy, Debug)]
+pub struct Sm2 {
+    /// The interval, in days, after the first successful review.
+    pub graduating_interval: u32,
+    /// The extra multiplier applied to the interval of cards graded Easy.
+    pub easy_bonus: f64,
+}
+
+impl Default for Sm2 {
+    fn default() -> Self {
+        Self {
+            graduating_interval: 1,
+            easy_bonus: SM2_EASY_BONUS,
+        }
+    }
+}
+
+impl Scheduler for Sm2 {
+    fn schedule(&self, card: &mut Card, grade: Grade, today: NaiveDate) {
+        if grade == Grade::Again {
+            if card.reps > 0 {
+                card.lapses += 1;
+            }
+            card.reps = 0;
+            card.ease = (card.ease - 0.2).max(SM2_MINIMUM_EASE);
+            card.interval = 0;
+            card.due = today;
+            return;
+        }
+
+        let q = 5.0 - grade.quality();
+        card.ease = (card.ease + 0.1 - q * (0.08 + q * 0.02)).max(SM2_MINIMUM_EASE);
+
+        let interval = match card.reps {
+            0 => match grade {
+                Grade::Easy => (self.graduating_interval as f64).max(4.0),
+                _ => self.graduating_interval as f64,
+            },
+            1 => match grade {
+                Grade::Hard => 3.0,
+                Grade::Easy => 6.0 * self.easy_bonus,
+                _ => 6.0,
+            },
+            _ => {
+                let previous = card.interval as f64;
+                match grade {
+                    Grade::Hard => previous * SM2_HARD_MULTIPLIER,
+                    Grade::Easy => previous * card.ease * self.easy_bonus,
+                    _ => previous * card.ease,
+                }
+            }
+        };
+
+        card.reps += 1;
+        card.interval = (interval.round() as u32).max(1);
+        card.due = today + Duration::days(card.interval as i64);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn today() -> NaiveDate {
+        NaiveDate::from_ymd_opt(2024, 3, 1).unwrap()
+    }
+
+    /// Reviews a new card with each of `grades` on the day it is due, returning it.
+    fn review(grades: &[Grade]) -> Card {
+        let mut card = Card::new("manger", "to eat", today());
+        for grade in grades {
+            let due = card.due;
+            Sm2::default().schedule(&mut card, *grade, due);
+        }
+        card
+    }
+
+    #[test]
+    fn good_reviews_space_out_by_ease() {
+        let intervals: Vec<u32> = (1..=4)
+            .map(|reviews| review(&vec![Grade::Good; reviews]).interval)
+            .collect();
+        assert_eq!(intervals, [1, 6, 15, 38]);
+
+        let card = review(&[Grade::Good; 3]);
+        assert_eq!(card.reps, 3);
+        assert_eq!(card.ease, SM2_INITIAL_EASE);
+        assert_eq!(card.due, today() + Duration::days(1 + 6 + 15));
+    }
+
+    #[test]
+    fn again_resets_the_card_and_lowers_its_ease() {
+        let card = review(&[Grade::Good, Grade::Good, Grade::Again]);
+        assert_eq!((card.reps, card.lapses, card.interval), (0, 1, 0));
+        assert!((card.ease - (SM2_INITIAL_EASE - 0.2)).abs() < 1e-9);
+        assert_eq!(card.due, today() + Duration::days(1 + 6));
+
+        // Forgetting a card that was never remembered isn't a lapse
+        assert_eq!(review(&[Grade::Again]).lapses, 0);
+    }
+
+    #[test]
+    fn hard_and_easy_adjust_interval_and_ease() {
+        let easy = review(&[Grade::Easy]);
+        assert_eq!(easy.interval, 4);
+        assert!((easy.ease - (SM2_INITIAL_EASE + 0.1)).abs() < 1e-9);
+
+        let hard = review(&[Grade::Good, Grade::Hard]);
+        assert_eq!(hard.interval, 3);
+        assert!((hard.ease - (SM2_INITIAL_EASE - 0.14)).abs() < 1e-9);
+    }
+
+    #[test]
+    fn ease_never_drops_below_the_minimum() {
+        let card = review(&[Grade::Again; 10]);
+        assert_eq!(card.ease, SM2_MINIMUM_EASE);
+    }
+}
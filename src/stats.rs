@@ -0,0 +1,189 @@
+use std::{collections::HashSet, fmt};
+
+use chrono::Duration;
+use ulid::Ulid;
+
+use crate::prelude::*;
+
+/// The interval, in days, from which a card counts as mature rather than still being learned.
+pub const MATURE_INTERVAL: u32 = 21;
+pub const STATS_HISTORY_DAYS: i64 = 30;
+
+/// A summary of the cards beneath a node and how their reviews have been going.
+#[derive(Clone, Debug)]
+pub struct Stats {
+    pub name: String,
+    pub due_today: usize,
+    pub due_tomorrow: usize,
+    pub due_this_week: usize,
+    pub new: usize,
+    pub learning: usize,
+    pub mature: usize,
+    /// The number of reviews on each of the last `STATS_HISTORY_DAYS` days, oldest first.
+    pub reviews_per_day: Vec<usize>,
+    /// The share of reviews of already-learned cards in that period that were remembered.
+    pub retention: Option<f64>,
+}
+
+impl Stats {
+    /// The stats of the cards beneath `node`, or only of those tagged `tag` if given, with
+    /// reviews in the `log` counted on the day `day` puts them on.
+    pub fn new(
+        node: &DeckNode,
+        tag: Option<&str>,
+        log: &[ReviewLogEntry],
+        day: &DaySettings,
+        today: NaiveDate,
+    ) -> Self {
+        let cards = tagged_cards(node, tag);
+        // Suspended cards never count as due, and buried ones only once they come back
+        let due_within = |days: i64| {
+            let until = today + Duration::days(days);
+            cards
+                .iter()
+                .filter(|card| card.due <= until && card.is_active(until))
+                .count()
+        };
+
+        let due_today = due_within(0);
+        let tomorrow = today + Duration::days(1);
+        let due_tomorrow = cards
+            .iter()
+            .filter(|card| card.due == tomorrow && card.is_active(tomorrow))
+            .count();
+        let due_this_week = due_within(6);
+
+        let new = cards
+            .iter()
+            .filter(|card| card.last_review.is_none())
+            .count();
+        let mature = cards
+            .iter()
+            .filter(|card| card.interval >= MATURE_INTERVAL)
+            .count();
+        let learning = cards.len() - new - mature;
+
+        let first_day = today - Duration::days(STATS_HISTORY_DAYS - 1);
+        let recent: Vec<&ReviewLogEntry> = reviews_beneath(node, tag, log)
+            .into_iter()
+            .filter(|entry| (first_day..=today).contains(&day.day_of(entry.time)))
+            .collect();
+
+        let mut reviews_per_day = vec![0; STATS_HISTORY_DAYS as usize];
+        for entry in &recent {
+            let index = (day.day_of(entry.time) - first_day).num_days() as usize;
+            reviews_per_day[index] += 1;
+        }
+
+        let (learned, remembered) = recent
+            .iter()
+            .filter(|entry| entry.previous_interval > 0)
+            .fold((0, 0), |(learned, remembered), entry| {
+                (
+                    learned + 1,
+                    remembered + (entry.grade != Grade::Again) as usize,
+                )
+            });
+        let retention = (learned > 0).then(|| remembered as f64 / learned as f64);
+
+        Self {
+            name: match tag {
+                Some(tag) => format!("{} #{}", node.display_name(), tag),
+                None => node.display_name(),
+            },
+            due_today,
+            due_tomorrow,
+            due_this_week,
+            new,
+            learning,
+            mature,
+            reviews_per_day,
+            retention,
+        }
+    }
+}
+
+impl fmt::Display for Stats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let total: usize = self.reviews_per_day.iter().sum();
+        let retention = match self.retention {
+            Some(retention) => format!("{:.1}%", retention * 100.0),
+            None => "-".to_owned(),
+        };
+
+        writeln!(f, "{}", self.name)?;
+        writeln!(f)?;
+        writeln!(f, "  Due today      {:>6}", self.due_today)?;
+        writeln!(f, "  Due tomorrow   {:>6}", self.due_tomorrow)?;
+        writeln!(f, "  Due this week  {:>6}", self.due_this_week)?;
+        writeln!(f)?;
+        writeln!(f, "  New            {:>6}", self.new)?;
+        writeln!(f, "  Learning       {:>6}", self.learning)?;
+        writeln!(f, "  Mature         {:>6}", self.mature)?;
+        writeln!(f)?;
+        writeln!(
+            f,
+            "  Reviews, last {} days: {} ({:.1}/day)",
+            STATS_HISTORY_DAYS,
+            total,
+            total as f64 / STATS_HISTORY_DAYS as f64
+        )?;
+        writeln!(f, "  {}", sparkline(&self.reviews_per_day))?;
+        writeln!(f)?;
+        write!(f, "  Retention      {:>6}", retention)
+    }
+}
+
+/// The cards beneath `node`, limited to those tagged `tag` if given.
+fn tagged_cards<'a>(node: &'a DeckNode, tag: Option<&str>) -> Vec<&'a Card> {
+    node.cards()
+        .into_iter()
+        .filter(|card| tag.is_none_or(|tag| card.has_tag(tag)))
+        .collect()
+}
+
+/// The entries of `log` reviewing a card beneath `node`, limited to cards tagged `tag` if
+/// given.
+pub fn reviews_beneath<'a>(
+    node: &DeckNode,
+    tag: Option<&str>,
+    log: &'a [ReviewLogEntry],
+) -> Vec<&'a ReviewLogEntry> {
+    let ids: HashSet<Ulid> = tagged_cards(node, tag).iter().map(|card| card.id).collect();
+    log.iter()
+        .filter(|entry| ids.contains(&entry.card))
+        .collect()
+}
+
+/// Draws each value as a block whose height is relative to the largest one.
+fn sparkline(values: &[usize]) -> String {
+    const BLOCKS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+    let max = values.iter().copied().max().unwrap_or(0);
+    values
+        .iter()
+        .map(|&value| match value {
+            0 => ' ',
+            _ => BLOCKS[(value * (BLOCKS.len() - 1)).div_ceil(max.max(1))],
+        })
+        .collect()
+}
+
+impl Collection {
+    /// Asks which node, and which of its tags if any, to show statistics for, then shows them.
+    pub fn prompt_stats(&self) -> InquireResult<()> {
+        let path = self.root.prompt_node(ENTER_STATS_NODE)?;
+        let Some(node) = self.root.at(path) else {
+            return Ok(());
+        };
+
+        let tag = node.prompt_tag_filter()?;
+        let tag = tag.as_deref();
+
+        let today = self.today();
+        let log = self.history();
+        let stats = Stats::new(node, tag, log, &self.day, today);
+        let heatmap = Heatmap::new(reviews_beneath(node, tag, log), &self.day, today);
+        println!("{}\n\n{}", stats, heatmap.render(color_enabled()));
+        Ok(())
+    }
+}
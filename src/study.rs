@@ -0,0 +1,192 @@
+use std::{collections::VecDeque, time::Instant};
+
+use chrono::Duration;
+use inquire::Select;
+use ulid::Ulid;
+
+use crate::prelude::*;
+
+/// How the user answers cards during a study session.
+#[derive(Clone, Copy, Debug)]
+pub enum StudyMode {
+    /// Reveal the answer, then grade yourself.
+    SelfGrade,
+    /// Type the answer, and have it compared with the expected one.
+    TypeAnswer(AnswerOptions),
+}
+
+impl StudyMode {
+    pub fn prompt_new() -> InquireResult<Self> {
+        match Select::new(ENTER_STUDY_MODE, vec![SELF_GRADE, TYPE_ANSWER]).prompt()? {
+            TYPE_ANSWER => Ok(Self::TypeAnswer(AnswerOptions::prompt_new()?)),
+            _ => Ok(Self::SelfGrade),
+        }
+    }
+}
+
+/// The cards left to show in a study session.
+struct Session {
+    /// Reviews, then new cards, in the order they are shown.
+    queue: VecDeque<Ulid>,
+    /// Cards in a learning step, along with when they are next due, soonest first.
+    learning: Vec<(DateTime<Utc>, Ulid)>,
+}
+
+impl Session {
+    /// A session over the cards in `queue`, which are beneath `node`.
+    fn new(queue: StudyQueue, node: &DeckNode) -> Self {
+        let mut session = Self {
+            queue: queue.review.into_iter().chain(queue.new).collect(),
+            learning: Vec::new(),
+        };
+        for id in queue.learning {
+            if let Some(due_at) = node.find_card(id).and_then(|card| card.due_at) {
+                session.learn(id, due_at);
+            }
+        }
+        session
+    }
+
+    /// Schedules the card with the given id to be shown again at `due_at`.
+    fn learn(&mut self, id: Ulid, due_at: DateTime<Utc>) {
+        let index = self.learning.partition_point(|(at, _)| *at <= due_at);
+        self.learning.insert(index, (due_at, id));
+    }
+
+    fn is_empty(&self) -> bool {
+        self.queue.is_empty() && self.learning.is_empty()
+    }
+
+    /// The next card to show at `now`: a card whose learning step has passed, or else the
+    /// next one in the queue. Once the queue runs out, cards due within the next
+    /// `LEARN_AHEAD_MINUTES` are shown early rather than waited for.
+    fn next(&mut self, now: DateTime<Utc>) -> Option<Ulid> {
+        let learn_ahead = now + Duration::minutes(LEARN_AHEAD_MINUTES);
+        match self.learning.first() {
+            Some((due_at, _))
+                if *due_at <= now || (self.queue.is_empty() && *due_at <= learn_ahead) =>
+            {
+                Some(self.learning.remove(0).1)
+            }
+            _ => self.queue.pop_front(),
+        }
+    }
+}
+
+impl Collection {
+    /// Reviews the cards due beneath the node at `path`, one at a time, saving after each.
+    /// Reviews come before new cards, and neither go past the daily limits of the presets
+    /// used at and beneath the node.
+    /// If the cards there are tagged, the session can be limited to those with a given tag.
+    /// Cards in a learning step are shown again once its delay has passed, and failed cards
+    /// without relearning steps are put back at the end of the queue, while those the user
+    /// buries or suspends are dropped from it without being graded.
+    pub fn prompt_study(&mut self, path: DeckPath) -> InquireResult<()> {
+        let day = self.day;
+        let today = day.today();
+        let Some(node) = self.root.at(path) else {
+            return Ok(());
+        };
+
+        let mut session = Session::new(self.study_queue(node.id(), None, today), node);
+        if !session.is_empty() {
+            // Filter before the daily limits are applied, so they are filled with tagged cards
+            if let Some(tag) = node.prompt_tag_filter()? {
+                session = Session::new(self.study_queue(node.id(), Some(&tag), today), node);
+            }
+        }
+        if session.is_empty() {
+            println!("{}", NOTHING_DUE);
+            return Ok(());
+        }
+
+        let mode = StudyMode::prompt_new()?;
+        while let Some(card_id) = session.next(Utc::now()) {
+            let Some(deck) = self.root.deck_of_mut(card_id).map(|deck| deck.id()) else {
+                // The card was removed since the session started
+                continue;
+            };
+            let preset = self.preset_for(deck).clone();
+            let Some(DeckNode::Deck { cards, leech, .. }) = self.root.find_mut(deck) else {
+                continue;
+            };
+            let Some(card) = cards.iter_mut().find(|card| card.id == card_id) else {
+                continue;
+            };
+
+            let (previous_interval, previous_ease, previous_lapses) =
+                (card.interval, card.ease, card.lapses);
+            let started = Instant::now();
+            let answer = match mode {
+                StudyMode::SelfGrade => card.prompt_ask()?,
+                StudyMode::TypeAnswer(options) => card.prompt_type(options, &self.note_types)?,
+            };
+            let duration = started.elapsed();
+
+            let grade = match answer {
+                Answer::Grade(grade) => grade,
+                Answer::Bury => {
+                    card.bury(today);
+                    self.save()?;
+                    continue;
+                }
+                Answer::Suspend => {
+                    card.suspended = true;
+                    self.save()?;
+                    continue;
+                }
+            };
+
+            let original = card.clone();
+            card.review(grade, Utc::now(), &day, &preset);
+            let leeched = card.lapses > previous_lapses && leech.check(card);
+            let leech_action = leech.action;
+            let entry = ReviewLogEntry {
+                card: card.id,
+                time: Utc::now(),
+                grade,
+                duration_ms: duration.as_millis() as u64,
+                previous_interval,
+                interval: card.interval,
+                previous_ease,
+                ease: card.ease,
+            };
+            let again = card
+                .is_active(today)
+                .then_some((card.due_at, card.is_due(today)));
+
+            // The log is the record of the review, so the card only changes once it is written
+            if let Err(err) = self.record_review(entry) {
+                if let Some(card) = self.root.find_card_mut(card_id) {
+                    *card = original;
+                }
+                eprintln!("{}: {}", REVIEW_NOT_LOGGED, err);
+                return Ok(());
+            }
+            self.save()?;
+
+            if leeched {
+                match leech_action {
+                    LeechAction::Tag => println!("{}", LEECH_TAGGED),
+                    LeechAction::Suspend => println!("{}", LEECH_SUSPENDED),
+                }
+            }
+            match again {
+                Some((Some(due_at), _)) => session.learn(card_id, due_at),
+                Some((None, true)) => session.queue.push_back(card_id),
+                _ => {}
+            }
+        }
+
+        match session.learning.first() {
+            Some((due_at, _)) => println!(
+                "{} {} {}",
+                session.learning.len(),
+                STILL_LEARNING,
+                day.local(*due_at).format("%H:%M")
+            ),
+            None => println!("{}", STUDY_DONE),
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,147 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fmt,
+};
+
+use inquire::{Select, Text};
+
+use crate::prelude::*;
+
+/// The tags in a list separated by spaces or commas, without any leading `#`.
+pub fn parse_tags(text: &str) -> BTreeSet<String> {
+    text.split(|c: char| c.is_whitespace() || c == ',')
+        .map(|tag| tag.trim_start_matches('#'))
+        .filter(|tag| !tag.is_empty())
+        .map(str::to_owned)
+        .collect()
+}
+
+/// The tags written out as `#one #two`.
+pub fn display_tags(tags: &BTreeSet<String>) -> String {
+    tags.iter()
+        .map(|tag| format!("#{}", tag))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Asks for a list of tags, starting from the `current` ones.
+pub fn prompt_tags(current: &BTreeSet<String>) -> InquireResult<BTreeSet<String>> {
+    let initial = current.iter().cloned().collect::<Vec<_>>().join(" ");
+    let text = Text::new(ENTER_TAGS)
+        .with_initial_value(&initial)
+        .with_help_message(HELP_TAGS)
+        .prompt()?;
+    Ok(parse_tags(&text))
+}
+
+/// A tag along with the number of cards carrying it.
+struct TagCount {
+    tag: String,
+    count: usize,
+}
+
+impl fmt::Display for TagCount {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "#{} ({})", self.tag, self.count)
+    }
+}
+
+fn tag_counts(tags: BTreeMap<String, usize>) -> Vec<TagCount> {
+    tags.into_iter()
+        .map(|(tag, count)| TagCount { tag, count })
+        .collect()
+}
+
+impl DeckNode {
+    /// Every tag used by a card beneath this node, with the number of cards using it.
+    pub fn tags(&self) -> BTreeMap<String, usize> {
+        let mut tags = BTreeMap::new();
+        for card in self.cards() {
+            for tag in &card.tags {
+                *tags.entry(tag.clone()).or_insert(0) += 1;
+            }
+        }
+        tags
+    }
+
+    /// Renames `old` to `new` on every card beneath this node, returning how many changed.
+    pub fn rename_tag(&mut self, old: &str, new: &str) -> usize {
+        let mut count = 0;
+        for card in self.cards_mut_all() {
+            if card.tags.remove(old) {
+                card.tags.insert(new.to_owned());
+                count += 1;
+            }
+        }
+        count
+    }
+
+    /// Removes `tag` from every card beneath this node, returning how many changed.
+    pub fn remove_tag(&mut self, tag: &str) -> usize {
+        let mut count = 0;
+        for card in self.cards_mut_all() {
+            if card.tags.remove(tag) {
+                count += 1;
+            }
+        }
+        count
+    }
+
+    /// Asks which tag to limit the cards beneath this node to, if they have any tags at all.
+    /// Returns `None` for all cards.
+    pub fn prompt_tag_filter(&self) -> InquireResult<Option<String>> {
+        let tags = self.tags();
+        if tags.is_empty() {
+            return Ok(None);
+        }
+
+        let mut options = vec![ALL_CARDS.to_owned()];
+        options.extend(tag_counts(tags).iter().map(ToString::to_string));
+        let choice = Select::new(ENTER_TAG_FILTER, options).raw_prompt()?;
+        Ok(choice
+            .index
+            .checked_sub(1)
+            .and_then(|i| self.tags().into_keys().nth(i)))
+    }
+}
+
+impl Collection {
+    /// Lists every tag in the collection, letting the user rename or delete them.
+    pub fn prompt_tags(&mut self) -> InquireResult<()> {
+        loop {
+            let tags = self.root.tags();
+            if tags.is_empty() {
+                println!("{}", NO_TAGS);
+                return Ok(());
+            }
+
+            let mut options: Vec<String> = tag_counts(tags.clone())
+                .iter()
+                .map(ToString::to_string)
+                .collect();
+            options.push(BACK.to_owned());
+            let choice = Select::new(TAGS, options).raw_prompt()?;
+            let Some(tag) = tags.into_keys().nth(choice.index) else {
+                return Ok(());
+            };
+
+            match Select::new(&format!("#{}", tag), vec![RENAME, REMOVE_TAG, BACK]).prompt()? {
+                RENAME => {
+                    let new = Text::new(&format!("#{} ->", tag))
+                        .with_initial_value(&tag)
+                        .prompt()?;
+                    let Some(new) = parse_tags(&new).into_iter().next() else {
+                        continue;
+                    };
+                    self.root.rename_tag(&tag, &new);
+                    self.save()?;
+                }
+                REMOVE_TAG if prompt_confirm()? => {
+                    self.root.remove_tag(&tag);
+                    self.save()?;
+                }
+                _ => {}
+            }
+        }
+    }
+}
@@ -0,0 +1,281 @@
+use inquire::{MultiSelect, Select, Text};
+use unicode_normalization::{char::is_combining_mark, UnicodeNormalization};
+
+use crate::prelude::*;
+
+const GREEN: &str = "\x1b[32m";
+const RED: &str = "\x1b[31;9m";
+const YELLOW: &str = "\x1b[33;4m";
+const RESET: &str = "\x1b[0m";
+
+/// Which differences between a typed answer and the expected one don't count as mistakes.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct AnswerOptions {
+    pub ignore_case: bool,
+    pub ignore_punctuation: bool,
+    /// Treats letters as equal to their unaccented forms, so "eleve" matches "élève".
+    pub ignore_accents: bool,
+}
+
+impl AnswerOptions {
+    pub fn prompt_new() -> InquireResult<Self> {
+        let options = vec![IGNORE_CASE, IGNORE_PUNCTUATION, IGNORE_ACCENTS];
+        let ignored = MultiSelect::new(ENTER_IGNORE, options)
+            .with_default(&[0, 1])
+            .prompt()?;
+        Ok(Self {
+            ignore_case: ignored.contains(&IGNORE_CASE),
+            ignore_punctuation: ignored.contains(&IGNORE_PUNCTUATION),
+            ignore_accents: ignored.contains(&IGNORE_ACCENTS),
+        })
+    }
+
+    /// The characters of `text` to compare, each paired with the form it is compared by.
+    fn keys(&self, text: &str) -> Vec<(char, char)> {
+        text.trim()
+            .chars()
+            .filter(|c| !(self.ignore_punctuation && c.is_ascii_punctuation()))
+            .map(|c| {
+                let mut key = c;
+                if self.ignore_accents {
+                    key = c.nfd().find(|d| !is_combining_mark(*d)).unwrap_or(c);
+                }
+                if self.ignore_case {
+                    key = key.to_lowercase().next().unwrap_or(key);
+                }
+                (c, key)
+            })
+            .collect()
+    }
+}
+
+/// A character of a typed answer compared to the expected answer.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DiffChar {
+    Correct(char),
+    /// Expected, but not typed.
+    Missing(char),
+    /// Typed, but not expected.
+    Extra(char),
+}
+
+/// A character-level comparison of a typed answer with the expected one.
+#[derive(Clone, Debug)]
+pub struct AnswerDiff {
+    pub chars: Vec<DiffChar>,
+}
+
+impl AnswerDiff {
+    /// Lines up `typed` with `expected` along their longest common subsequence.
+    pub fn new(typed: &str, expected: &str, options: AnswerOptions) -> Self {
+        let typed = options.keys(typed);
+        let expected = options.keys(expected);
+        let (n, m) = (typed.len(), expected.len());
+
+        // lengths[i][j] is the length of the longest common subsequence of typed[i..] and
+        // expected[j..]
+        let mut lengths = vec![vec![0; m + 1]; n + 1];
+        for i in (0..n).rev() {
+            for j in (0..m).rev() {
+                lengths[i][j] = if typed[i].1 == expected[j].1 {
+                    lengths[i + 1][j + 1] + 1
+                } else {
+                    lengths[i + 1][j].max(lengths[i][j + 1])
+                };
+            }
+        }
+
+        let mut chars = Vec::new();
+        let (mut i, mut j) = (0, 0);
+        while i < n || j < m {
+            if i < n && j < m && typed[i].1 == expected[j].1 {
+                chars.push(DiffChar::Correct(expected[j].0));
+                i += 1;
+                j += 1;
+            } else if j < m && (i == n || lengths[i][j + 1] >= lengths[i + 1][j]) {
+                chars.push(DiffChar::Missing(expected[j].0));
+                j += 1;
+            } else {
+                chars.push(DiffChar::Extra(typed[i].0));
+                i += 1;
+            }
+        }
+        Self { chars }
+    }
+
+    pub fn is_exact(&self) -> bool {
+        self.chars.iter().all(|c| matches!(c, DiffChar::Correct(_)))
+    }
+
+    /// The share of characters, out of the longer of the two answers, that were correct.
+    pub fn similarity(&self) -> f64 {
+        let count = |f: fn(&DiffChar) -> bool| self.chars.iter().filter(|c| f(c)).count();
+        let correct = count(|c| matches!(c, DiffChar::Correct(_)));
+        let missing = count(|c| matches!(c, DiffChar::Missing(_)));
+        let extra = count(|c| matches!(c, DiffChar::Extra(_)));
+
+        let longest = (correct + missing).max(correct + extra);
+        if longest == 0 {
+            return 1.0;
+        }
+        correct as f64 / longest as f64
+    }
+
+    /// The grade the comparison suggests: Good for an exact match, Hard for a near miss, and
+    /// Again otherwise.
+    pub fn suggested_grade(&self) -> Grade {
+        if self.is_exact() {
+            Grade::Good
+        } else if self.similarity() >= 0.8 {
+            Grade::Hard
+        } else {
+            Grade::Again
+        }
+    }
+
+    /// Draws the comparison, coloring correct, missing and extra characters if `color` is
+    /// set, and otherwise marking missing ones as `[+...]` and extra ones as `[-...]`.
+    pub fn render(&self, color: bool) -> String {
+        if color {
+            let mut out = String::new();
+            for c in &self.chars {
+                let (style, c) = match c {
+                    DiffChar::Correct(c) => (GREEN, c),
+                    DiffChar::Missing(c) => (YELLOW, c),
+                    DiffChar::Extra(c) => (RED, c),
+                };
+                out.push_str(style);
+                out.push(*c);
+            }
+            out.push_str(RESET);
+            return out;
+        }
+
+        let mut out = String::new();
+        let mut open: Option<char> = None;
+        for c in &self.chars {
+            let (mark, c) = match c {
+                DiffChar::Correct(c) => (None, c),
+                DiffChar::Missing(c) => (Some('+'), c),
+                DiffChar::Extra(c) => (Some('-'), c),
+            };
+            if mark != open {
+                if open.is_some() {
+                    out.push(']');
+                }
+                if let Some(mark) = mark {
+                    out.push('[');
+                    out.push(mark);
+                }
+                open = mark;
+            }
+            out.push(*c);
+        }
+        if open.is_some() {
+            out.push(']');
+        }
+        out
+    }
+}
+
+impl Card {
+    /// Shows the front of the card and has the user type the answer, then shows how it
+    /// compares to the expected answer and asks for a grade, suggesting one. Cards generated
+    /// from one of the `note_types` expect their back without the front.
+    pub fn prompt_type(
+        &self,
+        options: AnswerOptions,
+        note_types: &[NoteType],
+    ) -> InquireResult<Answer> {
+        let typed = Text::new(&text_block(&self.question()))
+            .with_help_message(HELP_TYPE_ANSWER)
+            .prompt()?;
+
+        let diff = AnswerDiff::new(&typed, &self.expected_answer(note_types), options);
+        let suggested = diff.suggested_grade();
+        let message = format!(
+            "{}\n    {}\n",
+            text_block(&self.answer()),
+            diff.render(color_enabled())
+        );
+
+        Select::new(&message, Answer::all())
+            .with_starting_cursor(Grade::ALL.iter().position(|g| *g == suggested).unwrap_or(2))
+            .prompt()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const STRICT: AnswerOptions = AnswerOptions {
+        ignore_case: false,
+        ignore_punctuation: false,
+        ignore_accents: false,
+    };
+
+    #[test]
+    fn accents_are_flagged_unless_ignored() {
+        let strict = AnswerDiff::new("eleve", "élève", STRICT);
+        assert!(!strict.is_exact());
+        assert_eq!(strict.render(false), "[+é][-e]l[+è][-e]ve");
+
+        let lenient = AnswerOptions {
+            ignore_accents: true,
+            ..STRICT
+        };
+        let diff = AnswerDiff::new("eleve", "élève", lenient);
+        assert!(diff.is_exact());
+        assert_eq!(diff.render(false), "élève");
+    }
+
+    #[test]
+    fn case_and_punctuation_can_be_ignored() {
+        assert!(!AnswerDiff::new("to eat", "To eat!", STRICT).is_exact());
+
+        let lenient = AnswerOptions {
+            ignore_case: true,
+            ignore_punctuation: true,
+            ..STRICT
+        };
+        assert!(AnswerDiff::new("to eat", "To eat!", lenient).is_exact());
+    }
+
+    #[test]
+    fn marks_missing_and_extra_characters() {
+        let diff = AnswerDiff::new("gravr", "gravir", STRICT);
+        assert_eq!(
+            diff.chars,
+            "grav"
+                .chars()
+                .map(DiffChar::Correct)
+                .chain([DiffChar::Missing('i'), DiffChar::Correct('r')])
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(
+            AnswerDiff::new("gravirr", "gravir", STRICT).render(false),
+            "gravir[-r]"
+        );
+    }
+
+    #[test]
+    fn suggests_a_grade_by_similarity() {
+        assert_eq!(
+            AnswerDiff::new("to climb", "to climb", STRICT).suggested_grade(),
+            Grade::Good
+        );
+        assert_eq!(
+            AnswerDiff::new("to clim", "to climb", STRICT).suggested_grade(),
+            Grade::Hard
+        );
+        assert_eq!(
+            AnswerDiff::new("to see", "to climb", STRICT).suggested_grade(),
+            Grade::Again
+        );
+        assert_eq!(
+            AnswerDiff::new("", "", STRICT).suggested_grade(),
+            Grade::Good
+        );
+    }
+}
@@ -1,7 +1,11 @@
-use std::{fmt, cmp::Ordering};
+use std::{
+    cmp::Ordering,
+    fmt,
+    io::{self, IsTerminal},
+};
 
 use inquire::{error::InquireResult, Select};
-use unicode_truncate::UnicodeTruncateStr;
+use unicode_truncate::{Alignment, UnicodeTruncateStr};
 
 pub const TEXT_WIDTH: usize = 30;
 
@@ -40,7 +44,26 @@ pub fn ellipsis(str: &str, width: usize) -> String {
     }
 }
 
-pub fn sort<T, F>(vec: &mut Vec<T>, compare: F)
+/// `str` on a single line, cut short with an ellipsis and padded with spaces to fill exactly
+/// `width` columns.
+pub fn column(str: &str, width: usize) -> String {
+    let line = str.replace('\n', " ");
+    ellipsis(&line, width.saturating_sub(3))
+        .unicode_pad(width, Alignment::Left, false)
+        .into_owned()
+}
+
+/// Whether output should be colored: only on a terminal, and never if `NO_COLOR` is set.
+pub fn color_enabled() -> bool {
+    std::env::var_os("NO_COLOR").is_none() && io::stdout().is_terminal()
+}
+
+pub fn text_block(str: &str) -> String {
+    let lines: Vec<String> = str.lines().map(|line| format!("    {}", line)).collect();
+    format!("\n{}\n", lines.join("\n"))
+}
+
+pub fn sort<T, F>(vec: &mut [T], compare: F)
 where
     F: FnMut(&T, &T) -> Ordering,
 {
@@ -49,29 +72,205 @@ where
 
 pub const MAIN_MENU: &str = "Main Menu";
 pub const STATS: &str = "Stats";
+pub const FORECAST: &str = "Forecast";
+pub const NOTE_TYPES: &str = "Note Types";
+pub const TAGS: &str = "Tags";
+pub const BROWSE: &str = "Browse";
+pub const LEECHES: &str = "Leeches";
+pub const SETTINGS: &str = "Settings";
 pub const QUIT: &str = "Quit";
 
+pub const STUDY: &str = "Study";
 pub const RENAME: &str = "Rename";
 pub const BACK: &str = "Back";
 
 pub const ADD_DECK: &str = "Add Deck";
 pub const ADD_SET: &str = "Add Set";
+pub const REORDER_ENTRIES: &str = "Reorder Entries";
+pub const MOVE_ENTRY: &str = "Move Entry";
 pub const REMOVE_ENTRIES: &str = "Remove Entries";
 
 pub const ADD_CARD: &str = "Add Card";
+pub const EDIT_CARDS: &str = "Edit Cards";
+pub const MOVE_CARDS: &str = "Move Cards";
 pub const REMOVE_CARDS: &str = "Remove Cards";
+pub const SCHEDULER: &str = "Scheduler";
+pub const OPTIMIZE: &str = "Optimize FSRS";
+pub const LEECH_HANDLING: &str = "Leech Handling";
+pub const OPTIONS: &str = "Options";
+
+pub const ENTER_SCHEDULER: &str = "Scheduler:";
+pub const SCHEDULER_SM2: &str = "SM-2";
+pub const SCHEDULER_FSRS: &str = "FSRS";
+pub const SCHEDULER_LEITNER: &str = "Leitner";
+pub const ENTER_DESIRED_RETENTION: &str = "Desired retention:";
+pub const HELP_DESIRED_RETENTION: &str =
+    "the chance of remembering a card you are aiming for, between 0.7 and 0.99";
+pub const INVALID_DESIRED_RETENTION: &str = "must be between 0.7 and 0.99";
+pub const ENTER_LEITNER_INTERVALS: &str = "Box intervals:";
+pub const HELP_LEITNER_INTERVALS: &str =
+    "the days between reviews for each box, separated by commas";
+pub const INVALID_LEITNER_INTERVALS: &str = "must be a list of whole numbers of days";
+
+pub const ENTER_LEECH_THRESHOLD: &str = "Leech threshold:";
+pub const HELP_LEECH_THRESHOLD: &str =
+    "the number of times a card is forgotten before it is a leech";
+pub const INVALID_LEECH_THRESHOLD: &str = "must be at least 1";
+pub const ENTER_LEECH_ACTION: &str = "When a card becomes a leech:";
+pub const LEECH_TAG_ONLY: &str = "Tag it";
+pub const LEECH_SUSPEND: &str = "Tag and suspend it";
+pub const ENTER_LEECHES_NODE: &str = "Show leeches in:";
+pub const NO_LEECHES: &str = "There are no leeches here.";
+pub const LEECH_TAGGED: &str = "That card is a leech, and has been tagged #leech.";
+pub const LEECH_SUSPENDED: &str = "That card is a leech, and has been tagged #leech and suspended.";
+
+pub const DEFAULT_PRESET_NAME: &str = "Default";
+pub const INHERIT_PRESET: &str = "Use the preset of the enclosing set";
+pub const USE_PRESET: &str = "Use preset";
+pub const NEW_PRESET: &str = "New Preset";
+pub const EDIT_PRESET: &str = "Edit Preset";
+pub const ENTER_PRESET_NAME: &str = "Preset name:";
+pub const PRESET_ASSIGNED: &str = "Assigned here";
+pub const PRESET_INHERITED: &str = "Inherited from the enclosing set";
+pub const PRESET_DECKS: &str = "decks use this preset";
+pub const NEW_PER_DAY: &str = "New cards/day";
+pub const REVIEWS_PER_DAY: &str = "Maximum reviews/day";
+pub const LEARNING_STEPS: &str = "Learning steps";
+pub const RELEARNING_STEPS: &str = "Relearning steps";
+pub const GRADUATING_INTERVAL: &str = "Graduating interval";
+pub const EASY_BONUS: &str = "Easy bonus";
+pub const MAXIMUM_INTERVAL: &str = "Maximum interval";
+pub const DESIRED_RETENTION: &str = "Desired retention";
+pub const ENTER_LEARNING_STEPS: &str = "Learning steps:";
+pub const HELP_LEARNING_STEPS: &str = "minutes between showings of a new card, separated by commas";
+pub const ENTER_RELEARNING_STEPS: &str = "Relearning steps:";
+pub const HELP_RELEARNING_STEPS: &str =
+    "minutes between showings of a forgotten card, separated by commas";
+pub const INVALID_LEARNING_STEPS: &str = "must be a list of whole numbers of minutes";
+pub const INVALID_EASY_BONUS: &str = "must be at least 1";
+pub const INVALID_DAYS: &str = "must be at least 1 day";
+
+pub const TIMEZONE: &str = "Timezone";
+pub const ROLLOVER_HOUR: &str = "Next day starts at";
+pub const SYSTEM_TIMEZONE: &str = "system";
+pub const ENTER_TIMEZONE: &str = "Timezone:";
+pub const HELP_TIMEZONE: &str =
+    "offset from UTC, e.g. UTC-8 or +05:30; leave empty for the system's";
+pub const INVALID_TIMEZONE: &str = "must be an offset like -08:00 of at most 14 hours";
+pub const ENTER_ROLLOVER_HOUR: &str = "Next day starts at hour:";
+pub const HELP_ROLLOVER_HOUR: &str = "cards due tomorrow become due from this hour of the night";
+pub const INVALID_ROLLOVER_HOUR: &str = "must be an hour from 0 to 23";
+
+pub const NO_FSRS_DECKS: &str = "No deck here is scheduled with FSRS.";
+pub const NOT_ENOUGH_REVIEWS: &str = "Not enough reviews to optimize from yet.";
+pub const OPTIMIZE_NO_IMPROVEMENT: &str = "The current parameters already fit best; kept them.";
+pub const OPTIMIZE_SAVED: &str = "Saved the optimized parameters.";
 
 pub const ENTER_REMOVE: &str = "Remove:";
+pub const ENTER_EDIT_CARD: &str = "Edit:";
+pub const ENTER_REORDER: &str = "Reorder:";
+pub const ENTER_DIRECTION: &str = "Direction:";
+pub const MOVE_UP: &str = "Up";
+pub const MOVE_DOWN: &str = "Down";
+pub const ENTER_MOVE: &str = "Move:";
+pub const ENTER_MOVE_TO: &str = "Move to:";
+pub const NO_MOVE_DESTINATION: &str = "There is nowhere to move that to.";
+pub const INVALID_MOVE: &str = "A set can't be moved into itself.";
+
+pub const PREVIEW: &str = "Preview";
+pub const EDIT_FIELDS: &str = "Edit Fields";
+pub const EDIT_FRONT: &str = "Edit Front";
+pub const EDIT_BACK: &str = "Edit Back";
+pub const EDIT_TAGS: &str = "Edit Tags";
+pub const EDIT_DUE: &str = "Edit Due Date";
+pub const SUSPEND: &str = "Suspend";
+pub const UNSUSPEND: &str = "Unsuspend";
+pub const BURY: &str = "Bury until tomorrow";
+pub const UNBURY: &str = "Unbury";
+pub const DUE: &str = "Due:";
 
 pub const ENTER_DECK_NAME: &str = "Deck name:";
 pub const ENTER_SET_NAME: &str = "Set name:";
 
+pub const ENTER_CARD_TYPE: &str = "Card type:";
+pub const CARD_BASIC: &str = "Basic";
+pub const CARD_BIDIRECTIONAL: &str = "Bidirectional";
+pub const CARD_CLOZE: &str = "Cloze";
+
+pub const ENTER_CLOZE_TEXT: &str = "Text:";
+pub const HELP_CLOZE_TEXT: &str = "hide parts of it like {{c1::answer}} or {{c1::answer::hint}}";
+pub const INVALID_CLOZE_TEXT: &str = "must contain at least one {{c1::...}} deletion";
+pub const ENTER_CLOZE_EXTRA: &str = "Extra:";
+pub const HELP_CLOZE_EXTRA: &str = "shown along with the answer, may be left empty";
+
+pub const ADD_NOTE_TYPE: &str = "Add Note Type";
+pub const ADD_FIELD: &str = "Add Field";
+pub const RENAME_FIELD: &str = "Rename Field";
+pub const ADD_TEMPLATE: &str = "Add Template";
+pub const EDIT_TEMPLATE: &str = "Edit Template";
+pub const ENTER_NOTE_TYPE_NAME: &str = "Note type name:";
+pub const ENTER_NOTE_FIELDS: &str = "Fields:";
+pub const HELP_NOTE_FIELDS: &str = "the names of the note's fields, separated by commas";
+pub const INVALID_NOTE_FIELDS: &str = "must be at least one name, with none repeated";
+pub const ENTER_FIELD: &str = "Field:";
+pub const ENTER_FIELD_NAME: &str = "Field name:";
+pub const ENTER_TEMPLATE: &str = "Template:";
+pub const ENTER_TEMPLATE_NAME: &str = "Template name:";
+pub const ENTER_TEMPLATE_FRONT: &str = "Front template:";
+pub const HELP_TEMPLATE_FRONT: &str = "write {{Field}} where a field's value should go";
+pub const ENTER_TEMPLATE_BACK: &str = "Back template:";
+pub const HELP_TEMPLATE_BACK: &str = "write {{Field}} for a field, or {{FrontSide}} for the front";
+
 pub const ENTER_CARD_FRONT: &str = "Front:";
 pub const HELP_CARD_FRONT: &str = "the text initially revealed to you";
 
 pub const ENTER_CARD_BACK: &str = "Back:";
 pub const HELP_CARD_BACK: &str = "the text you are shown afterwards";
 
+pub const ENTER_TAGS: &str = "Tags:";
+pub const HELP_TAGS: &str = "separated by spaces, may be left empty";
+pub const ENTER_TAG_FILTER: &str = "Only cards tagged:";
+pub const ALL_CARDS: &str = "Any (all cards)";
+pub const REMOVE_TAG: &str = "Delete";
+pub const NO_TAGS: &str = "No card has any tags yet.";
+
+pub const ENTER_QUERY: &str = "Search:";
+pub const HELP_QUERY: &str =
+    "e.g. deck:French::Vocab tag:verb due:<3 front:*ir \"to climb\" -is:suspended";
+pub const NO_MATCHES: &str = "No cards match that search.";
+pub const ENTER_SELECT_CARDS: &str = "Select cards";
+pub const ENTER_BULK_ACTION: &str = "With the selected cards:";
+pub const ADD_TAGS: &str = "Add Tags";
+pub const REMOVE_TAGS: &str = "Remove Tags";
+pub const RESCHEDULE: &str = "Reschedule";
+pub const ENTER_DUE: &str = "Due on:";
+
+pub const HELP_REVEAL: &str = "press enter to reveal the back";
+pub const AGAIN: &str = "Again";
+pub const HARD: &str = "Hard";
+pub const GOOD: &str = "Good";
+pub const EASY: &str = "Easy";
+pub const HELP_TYPE_ANSWER: &str = "type the answer, then press enter";
+pub const ENTER_STUDY_MODE: &str = "Answer by:";
+pub const SELF_GRADE: &str = "Revealing it and grading yourself";
+pub const TYPE_ANSWER: &str = "Typing it in";
+pub const ENTER_IGNORE: &str = "Ignore differences in:";
+pub const IGNORE_CASE: &str = "Case";
+pub const IGNORE_PUNCTUATION: &str = "Punctuation";
+pub const IGNORE_ACCENTS: &str = "Accents";
+pub const NOTHING_DUE: &str = "Nothing is due here today.";
+pub const STUDY_DONE: &str = "All done for today!";
+pub const REVIEW_NOT_LOGGED: &str = "couldn't write to the review log, so the review was undone";
+pub const LEARNING: &str = "learning";
+pub const STILL_LEARNING: &str = "cards are still being learned. Come back for the next one at";
+
+pub const ENTER_STATS_NODE: &str = "Show stats for:";
+
+pub const ENTER_FORECAST_NODE: &str = "Forecast reviews for:";
+pub const ENTER_FORECAST_DAYS: &str = "Days ahead:";
+pub const ASK_SIMULATE: &str = "Simulate future reviews?";
+pub const HELP_SIMULATE: &str = "count cards again when they come back after being reviewed";
+
 pub const ASK_CONFIRM: &str = "Are you sure?";
 pub const NO: &str = "No";
 pub const YES: &str = "Yes";